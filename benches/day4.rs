@@ -0,0 +1,178 @@
+use advent2024::days::day4::{self, Directions};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// The letter lookups both parts used before the grid was stored as bytes, copied verbatim
+/// from `src/days/day4.rs` as it was then, with only `pub` added to call them from here
+#[allow(clippy::all, clippy::pedantic, clippy::nursery)]
+mod baseline {
+    fn check_pos(grid: &Vec<&str>, x: i64, y: i64, c: char) -> bool {
+        // Check bounds
+        if y < 0 || x < 0 {
+            return false;
+        }
+
+        let x: usize = x.try_into().unwrap();
+        let y: usize = y.try_into().unwrap();
+
+        if y >= grid.len() || x >= grid[0].len() {
+            return false;
+        }
+
+        if let Some(v) = grid[y].chars().nth(x) {
+            v == c
+        } else {
+            false
+        }
+    }
+
+    pub fn find_char_coords(grid: &Vec<&str>, c: char) -> Vec<(i64, i64)> {
+        let mut xs = vec![];
+        for y in 0..grid.len() {
+            for x in 0..grid[0].len() {
+                if grid[y].chars().nth(x).unwrap() == c {
+                    xs.push((x as i64, y as i64));
+                }
+            }
+        }
+        xs
+    }
+
+    pub fn count_mas(grid: &Vec<&str>, xy: (i64, i64)) -> u64 {
+        let mut num_mas = 0;
+        let dirs = vec![
+            (0, 1),
+            (1, 0),
+            (0, -1),
+            (-1, 0),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ];
+        let (x, y) = xy;
+        let mas_chars = "MAS";
+
+        for (dx, dy) in dirs {
+            let mut curr_x = x;
+            let mut curr_y = y;
+            let mut found = true;
+            for c in mas_chars.chars() {
+                curr_x += dx;
+                curr_y += dy;
+                if !check_pos(&grid, curr_x, curr_y, c) {
+                    found = false;
+                    break;
+                }
+            }
+
+            if found {
+                num_mas += 1
+            };
+        }
+
+        num_mas
+    }
+
+    pub fn count_crosses(grid: &Vec<&str>, xy: (i64, i64)) -> u64 {
+        let (x, y) = xy;
+        let mut num_crosses = 0;
+        let directions = vec![(1, 1), (0, -2), (-2, 0), (0, 2)];
+        let mas_chars_type1 = "MMSS";
+        let mas_chars_type2 = "SSMM";
+        let mas_chars_type3 = "MSSM";
+        let mas_chars_type4 = "SMMS";
+        let mas_chars_types = vec![
+            mas_chars_type1,
+            mas_chars_type2,
+            mas_chars_type3,
+            mas_chars_type4,
+        ];
+
+        for mas_chars in mas_chars_types {
+            let mut curr_x = x;
+            let mut curr_y = y;
+            let mut found = true;
+
+            for ((dx, dy), c) in directions.iter().zip(mas_chars.chars()) {
+                curr_x += dx;
+                curr_y += dy;
+                if !check_pos(&grid, curr_x, curr_y, c) {
+                    found = false;
+                    break;
+                }
+            }
+            if found {
+                num_crosses += 1
+            };
+        }
+
+        num_crosses
+    }
+}
+
+/// Both parts on a 1000 by 1000 word search
+fn search(c: &mut Criterion) {
+    let text = day4::generate(2024, 1000);
+    let grid = day4::parse_input(&text).unwrap();
+
+    let mut group = c.benchmark_group("day4");
+    group.bench_function("parse", |b| b.iter(|| day4::parse_input(&text).unwrap()));
+    group.bench_function("xmas", |b| {
+        b.iter(|| day4::find_word(&grid, "XMAS", Directions::All, false).unwrap());
+    });
+    group.bench_function("x_mas", |b| {
+        b.iter(|| day4::find_stencil(&grid, "M.S\n.A.\nM.S", false).unwrap());
+    });
+    group.finish();
+}
+
+/// Both parts reading letters from the grid's bytes, or with the old `chars().nth(x)` lookups
+fn lookups(c: &mut Criterion) {
+    let text = day4::generate(2024, 1000);
+    let grid = day4::parse_input(&text).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    let old_words = |lines: &Vec<&str>| -> u64 {
+        baseline::find_char_coords(lines, 'X')
+            .iter()
+            .map(|&xy| baseline::count_mas(lines, xy))
+            .sum()
+    };
+    let old_crosses = |lines: &Vec<&str>| -> u64 {
+        baseline::find_char_coords(lines, 'A')
+            .iter()
+            .map(|&xy| baseline::count_crosses(lines, xy))
+            .sum()
+    };
+
+    // Both ways have to find the same words to be worth comparing
+    let words = day4::find_word(&grid, "XMAS", Directions::All, false).unwrap();
+    let crosses = day4::find_stencil(&grid, "M.S\n.A.\nM.S", false).unwrap();
+    assert_eq!(old_words(&lines), u64::try_from(words.len()).unwrap());
+    assert_eq!(old_crosses(&lines), u64::try_from(crosses.len()).unwrap());
+
+    compare(
+        c,
+        "day4_xmas",
+        || day4::find_word(&grid, "XMAS", Directions::All, false).unwrap(),
+        || old_words(&lines),
+    );
+    compare(
+        c,
+        "day4_x_mas",
+        || day4::find_stencil(&grid, "M.S\n.A.\nM.S", false).unwrap(),
+        || old_crosses(&lines),
+    );
+}
+
+/// Time the new way of solving a part against the old `chars().nth(x)` one, which is slow
+/// enough to only take a few samples of
+fn compare<T, U>(c: &mut Criterion, name: &str, bytes: impl Fn() -> T, chars_nth: impl Fn() -> U) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    group.bench_function("bytes", |b| b.iter(&bytes));
+    group.bench_function("chars_nth", |b| b.iter(&chars_nth));
+    group.finish();
+}
+
+criterion_group!(benches, search, lookups);
+criterion_main!(benches);
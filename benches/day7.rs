@@ -0,0 +1,85 @@
+use advent2024::days::day7::{self, PART2_OPERATORS};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::collections::VecDeque;
+
+/// The forward search part 2 used before the solvers took any operators, copied verbatim from
+/// `src/days/day7.rs` as it was then, with only `pub` added to call it from here
+#[allow(clippy::all, clippy::pedantic, clippy::nursery)]
+mod baseline {
+    use std::collections::VecDeque;
+
+    fn concat_nums(a: u64, b: u64) -> Option<u64> {
+        // Fails to parse (rather than wrapping) when the result is too big for a u64
+        format!("{a}{b}").parse().ok()
+    }
+
+    pub fn valid_equation(target: u64, mut ns: VecDeque<u64>, test: u64, is_part2: bool) -> bool {
+        if let Some(n) = ns.pop_front() {
+            // Recursive case
+            if test > target {
+                // Can return early if test is already too large
+                false
+            } else {
+                // Ugly, but on the first iteration should have test = 1 for mult
+                let test_mult_branch = if test == 0 { 1 } else { test };
+
+                // Overflowing branches can't reach the target since none of the operators decrease
+                let valid_branch = |next: Option<u64>| {
+                    next.is_some_and(|next| valid_equation(target, ns.clone(), next, is_part2))
+                };
+
+                valid_branch(test.checked_add(n))
+                    || valid_branch(test_mult_branch.checked_mul(n))
+                    || (is_part2 && valid_branch(concat_nums(test, n)))
+            }
+        } else {
+            // Base case where there are no more numbers
+            test == target
+        }
+    }
+}
+
+/// Part 2 of a puzzle sized input, working forward from the first number or back from the
+/// target, and with the original forward search that cloned the rest of the numbers each step
+fn solvers(c: &mut Criterion) {
+    let text = day7::generate(2024, 850);
+    let equations = day7::parse_input(&text).unwrap();
+
+    let deques: Vec<(u64, VecDeque<u64>)> = equations
+        .iter()
+        .map(|(test, ns)| (*test, ns.iter().copied().collect()))
+        .collect();
+    let valid_equations = || {
+        deques
+            .iter()
+            .filter(|(test, ns)| baseline::valid_equation(*test, ns.clone(), 0, true))
+            .count()
+    };
+
+    // The solvers have to agree on which equations can be made true to be worth comparing
+    let solved = equations
+        .iter()
+        .filter(|(test, ns)| day7::solve_backward(*test, ns, &PART2_OPERATORS).is_some())
+        .count();
+    assert_eq!(valid_equations(), solved);
+
+    let mut group = c.benchmark_group("day7_part2");
+    group.bench_function("valid_equation", |b| b.iter(valid_equations));
+    for (name, solve) in [
+        ("forward", day7::solve_forward as day7::Solver),
+        ("backward", day7::solve_backward),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                equations
+                    .iter()
+                    .filter(|(test, ns)| solve(*test, ns, &PART2_OPERATORS).is_some())
+                    .count()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, solvers);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+#![no_main]
+
+use advent2024::days::day11;
+use advent2024_fuzz::solve;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Stones are counted by value, so the work grows with the number of distinct stones rather
+    // than the length of the line, but every input is still blinked 75 times
+    if data.len() > 1024 {
+        return;
+    }
+
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = day11::parse_input(text);
+    }
+
+    solve(data, &[day11::part1, day11::part2]);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use advent2024::days::day13;
+use advent2024_fuzz::solve;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = day13::parse_input(text);
+    }
+
+    solve(data, &[day13::part1, day13::part2]);
+});
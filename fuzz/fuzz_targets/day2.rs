@@ -0,0 +1,13 @@
+#![no_main]
+
+use advent2024::days::day2;
+use advent2024_fuzz::solve;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = day2::parse_input(text);
+    }
+
+    solve(data, &[day2::part1, day2::part2]);
+});
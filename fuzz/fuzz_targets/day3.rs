@@ -0,0 +1,9 @@
+#![no_main]
+
+use advent2024::days::day3;
+use advent2024_fuzz::solve;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    solve(data, &[day3::part1, day3::part2]);
+});
@@ -0,0 +1,18 @@
+#![no_main]
+
+use advent2024::days::day6;
+use advent2024_fuzz::solve;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Part 2 re-walks the guard's route once for every open square
+    if data.len() > 4096 {
+        return;
+    }
+
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = day6::parse_input(text);
+    }
+
+    solve(data, &[day6::part1, day6::part2]);
+});
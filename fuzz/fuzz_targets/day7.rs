@@ -0,0 +1,20 @@
+#![no_main]
+
+use advent2024::days::day7;
+use advent2024_fuzz::solve;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Every operator is tried between each pair of operands, so keep the equations short
+    let short_equations = std::str::from_utf8(data)
+        .map_or(true, |text| text.lines().all(|l| l.split(' ').count() <= 10));
+    if !short_equations {
+        return;
+    }
+
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = day7::parse_input(text);
+    }
+
+    solve(data, &[day7::part1, day7::part2]);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use advent2024::days::day8;
+use advent2024_fuzz::solve;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = day8::parse_input(text);
+    }
+
+    solve(data, &[day8::part1, day8::part2]);
+});
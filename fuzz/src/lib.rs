@@ -0,0 +1,18 @@
+use advent2024::answer::Answer;
+use advent2024::error::Result;
+use std::path::Path;
+
+/// A solver for one part of a day, as called by the python bindings
+pub type Part = fn(&Path) -> Result<Answer>;
+
+/// Write the fuzzed bytes to an input file and run each part on it.
+/// Errors are fine, only panics (and hangs) are bugs
+pub fn solve(data: &[u8], parts: &[Part]) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data_path = temp_dir.path().join("input.txt");
+    std::fs::write(&data_path, data).unwrap();
+
+    for part in parts {
+        let _ = part(&data_path);
+    }
+}
@@ -0,0 +1,47 @@
+from advent2024 import advent2024
+import argparse
+import pathlib
+
+GENERATORS = {
+    1: advent2024.day1_generate,
+    2: advent2024.day2_generate,
+    3: advent2024.day3_generate,
+    4: advent2024.day4_generate,
+    5: advent2024.day5_generate,
+    6: advent2024.day6_generate,
+    7: advent2024.day7_generate,
+    8: advent2024.day8_generate,
+    9: advent2024.day9_generate,
+   10: advent2024.day10_generate,
+   11: advent2024.day11_generate,
+   12: advent2024.day12_generate,
+   13: advent2024.day13_generate,
+   15: advent2024.day15_generate,
+   25: advent2024.day25_generate,
+}
+
+SOLVABLE = {"mixed": None, "always": True, "never": False}
+
+def main():
+    parser = argparse.ArgumentParser(description="Generate random inputs for Advent of Code 2024")
+    parser.add_argument("--days", type=int, nargs="+", default=list(GENERATORS), choices=list(GENERATORS), help="Which days to generate inputs for, defaults to every implemented day")
+    parser.add_argument("--size", type=int, default=100, help="Size of each input, e.g. number of lines or the width of a grid depending on the day")
+    parser.add_argument("--seed", type=int, default=2024, help="Random seed, the same seed and size always give the same input")
+    parser.add_argument("--solvable", choices=list(SOLVABLE), default="mixed", help="Whether the day 13 claw machines can be won")
+    parser.add_argument("--out", type=str, default="data", help="Folder to write the inputs to as data/day1.txt data/day2.txt etc. so they can be passed to advent2024_run_all --data")
+    args = parser.parse_args()
+
+    out = pathlib.Path(args.out)
+    out.mkdir(parents=True, exist_ok=True)
+
+    for day in args.days:
+        if day == 13:
+            text = GENERATORS[day](args.seed, args.size, SOLVABLE[args.solvable])
+        else:
+            text = GENERATORS[day](args.seed, args.size)
+
+        (out / f"day{day}.txt").write_text(text)
+        print(f"Day {day} input written to {out / f'day{day}.txt'}")
+
+if __name__ == "__main__":
+    main()
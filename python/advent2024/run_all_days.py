@@ -10,11 +10,36 @@ def timer():
     yield
     print(f"({(time.perf_counter() - start)*1000:.2f}ms)")
 
+# The puzzle constants each day lets you change, along with their default values
+OPTIONS = {
+    2: advent2024.day2_options(),
+   10: advent2024.day10_options(),
+   11: advent2024.day11_options(),
+   13: advent2024.day13_options(),
+}
+
+def setting(s):
+    key, sep, value = s.partition("=")
+    if not sep:
+        raise argparse.ArgumentTypeError(f"expected key=value, found {s!r}")
+    return key, value
+
 def main():
+    all_options = sorted({key for options in OPTIONS.values() for key in options})
+
     parser = argparse.ArgumentParser(description="Run all days of Advent of Code 2024")
     parser.add_argument("--data", type=str, default="data", help="Folder containing input data files like data/ where it expects to find data/day1.txt data/day2.txt etc.")
+    parser.add_argument("--set", type=setting, action="append", default=[], metavar="KEY=VALUE", help=f"Change a puzzle constant for every day that has it, one of {', '.join(all_options)}")
     args = parser.parse_args()
 
+    day_options = {day: {} for day in OPTIONS}
+    for key, value in args.set:
+        if key not in all_options:
+            parser.error(f"unknown option {key!r}, expected one of {', '.join(all_options)}")
+        for day, options in OPTIONS.items():
+            if key in options:
+                day_options[day][key] = value
+
     days = [
         (1, [advent2024.day1_part1, advent2024.day1_part2]),
         (2, [advent2024.day2_part1, advent2024.day2_part2]),
@@ -35,7 +60,7 @@ def main():
     for day, funcs in days:
         for part,func in enumerate(funcs):
             with timer():
-                result = func(f"{args.data}/day{day}.txt")
+                result = func(f"{args.data}/day{day}.txt", **day_options.get(day, {}))
                 print(f"Day {day} part {part} result {result}", end=" ")
 
 if __name__ == "__main__":
@@ -0,0 +1,114 @@
+use crate::error::{Error, Result};
+use num_bigint::BigUint;
+use pyo3::prelude::*;
+use std::fmt;
+
+/// A puzzle answer, which converts to a plain Python `int` (or `str`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    U64(u64),
+    I128(i128),
+    Big(BigUint),
+    Str(String),
+}
+
+impl From<u64> for Answer {
+    fn from(n: u64) -> Self {
+        Self::U64(n)
+    }
+}
+
+impl From<i128> for Answer {
+    fn from(n: i128) -> Self {
+        // Keep non-negative answers as u64 so they compare equal however they were computed
+        u64::try_from(n).map_or(Self::I128(n), Self::U64)
+    }
+}
+
+impl From<BigUint> for Answer {
+    fn from(n: BigUint) -> Self {
+        u64::try_from(&n).map_or(Self::Big(n), Self::U64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Self::Str(s)
+    }
+}
+
+impl PartialEq<u64> for Answer {
+    fn eq(&self, other: &u64) -> bool {
+        match self {
+            Self::U64(n) => n == other,
+            Self::I128(n) => *n == i128::from(*other),
+            Self::Big(_) | Self::Str(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::U64(n) => write!(f, "{n}"),
+            Self::I128(n) => write!(f, "{n}"),
+            Self::Big(n) => write!(f, "{n}"),
+            Self::Str(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl<'py> IntoPyObject<'py> for Answer {
+    type Target = PyAny;
+    type Output = Bound<'py, PyAny>;
+    type Error = PyErr;
+
+    fn into_pyobject(self, py: Python<'py>) -> std::result::Result<Self::Output, Self::Error> {
+        Ok(match self {
+            Self::U64(n) => n.into_pyobject(py)?.into_any(),
+            Self::I128(n) => n.into_pyobject(py)?.into_any(),
+            Self::Big(n) => n.into_pyobject(py)?.into_any(),
+            Self::Str(s) => s.into_pyobject(py)?.into_any(),
+        })
+    }
+}
+
+/// Sum the values, reporting an overflow rather than wrapping or panicking
+pub fn checked_sum<I: IntoIterator<Item = u64>>(ns: I) -> Result<u64> {
+    ns.into_iter()
+        .try_fold(0_u64, u64::checked_add)
+        .ok_or(Error::Overflow)
+}
+
+/// Convert any count or index into a u64, reporting values that don't fit
+pub fn to_u64<T: TryInto<u64>>(n: T) -> Result<u64> {
+    n.try_into().map_err(|_| Error::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_negative_i128_is_u64() {
+        assert_eq!(Answer::from(31_i128), Answer::U64(31));
+        assert_eq!(Answer::from(-31_i128), Answer::I128(-31));
+        assert_eq!(
+            Answer::from(i128::from(u64::MAX) + 1),
+            Answer::I128(1 << 64)
+        );
+    }
+
+    #[test]
+    fn test_small_big_uint_is_u64() {
+        assert_eq!(Answer::from(BigUint::from(31_u64)), Answer::U64(31));
+        let big = BigUint::from(u64::MAX) + 1_u64;
+        assert_eq!(Answer::from(big.clone()), Answer::Big(big));
+    }
+
+    #[test]
+    fn test_checked_sum_overflow() {
+        assert_eq!(checked_sum([1, 2, 3]).unwrap(), 6);
+        assert!(matches!(checked_sum([u64::MAX, 1]), Err(Error::Overflow)));
+    }
+}
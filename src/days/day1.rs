@@ -1,57 +1,89 @@
-use std::cmp::max;
+use crate::answer::{checked_sum, Answer};
+use crate::error::{Error, Result};
+use crate::parse::number;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::path::Path;
 
-pub fn part1(data_path: &Path) -> u32 {
-    // Read entire file contents at once
-    let text = std::fs::read_to_string(data_path).unwrap();
+/// Split the two columns of location ids into a left and right list
+pub fn parse_input(text: &str) -> Result<(Vec<i64>, Vec<i64>)> {
+    let mut v1 = Vec::new();
+    let mut v2 = Vec::new();
+
+    for (i, l) in text.lines().enumerate() {
+        let ns: Vec<&str> = l.split_whitespace().collect();
+        let [n1, n2] = ns[..] else {
+            return Err(Error::Parse(format!(
+                "line {}: expected two location ids, found {}",
+                i + 1,
+                ns.len()
+            )));
+        };
+        v1.push(number(n1, i)?);
+        v2.push(number(n2, i)?);
+    }
+
+    Ok((v1, v2))
+}
 
-    // Convert lines to numeric
-    let nums: Vec<Vec<i32>> = text
-        .lines()
-        .map(|l| l.split_whitespace().map(|n| n.parse().unwrap()).collect())
-        .collect();
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    // Read entire file contents at once
+    let text = std::fs::read_to_string(data_path)?;
 
     // Collect the first and second value of each row into two sorted vectors
-    let mut v1: Vec<i32> = nums.iter().map(|ns| ns[0]).collect();
-    let mut v2: Vec<i32> = nums.iter().map(|ns| ns[1]).collect();
-    v1.sort();
-    v2.sort();
+    let (mut v1, mut v2) = parse_input(&text)?;
+    v1.sort_unstable();
+    v2.sort_unstable();
 
     // Perform the difference and sum
-    let diff: i32 = v1
-        .iter()
-        .zip(v2.iter())
-        .map(|(l, r)| max(l - r, r - l))
-        .sum();
+    let diff = checked_sum(v1.iter().zip(v2.iter()).map(|(l, r)| l.abs_diff(*r)))?;
 
-    diff.try_into().unwrap()
+    Ok(diff.into())
 }
 
-pub fn part2(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
-
-    let nums: Vec<Vec<i32>> = text
-        .lines()
-        .map(|l| l.split_whitespace().map(|n| n.parse().unwrap()).collect())
-        .collect();
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    let v1: Vec<i32> = nums.iter().map(|ns| ns[0]).collect();
-    let v2: Vec<i32> = nums.iter().map(|ns| ns[1]).collect();
+    let (v1, v2) = parse_input(&text)?;
 
-    let mut v2_counts: HashMap<i32, i32> = HashMap::new();
+    let mut v2_counts: HashMap<i64, u64> = HashMap::new();
     for v in v2 {
         *v2_counts.entry(v).or_insert(0) += 1;
     }
 
-    let diff: i32 = v1
-        .iter()
-        .map(|v| *v2_counts.entry(*v).or_insert(0) * v)
-        .sum();
-    diff.try_into().unwrap()
+    // Negative ids are allowed, so the similarity is accumulated as an i128
+    let mut similarity: i128 = 0;
+    for v in &v1 {
+        let count = i128::from(v2_counts.get(v).copied().unwrap_or(0));
+        similarity = count
+            .checked_mul(i128::from(*v))
+            .and_then(|s| similarity.checked_add(s))
+            .ok_or(Error::Overflow)?;
+    }
+
+    Ok(similarity.into())
+}
+
+/// Generate a random pair of location lists with `size` rows
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut text = String::new();
+
+    // Only draw from as many ids as there are rows so that some repeat for part 2
+    let max_id = 10_000 + size;
+    for _ in 0..size {
+        let l = rng.gen_range(10_000..max_id);
+        let r = rng.gen_range(10_000..max_id);
+        writeln!(text, "{l}   {r}").unwrap();
+    }
+
+    text
 }
 
 // Test the run function
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,14 +113,37 @@ mod tests {
     #[test]
     fn test_part1() {
         let (_d, _f, test_path) = create_test_file();
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 11);
     }
 
     #[test]
     fn test_part2() {
         let (_d, _f, test_path) = create_test_file();
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 31);
     }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 1000);
+        assert_eq!(test_input, generate(2024, 1000));
+        assert_eq!(test_input.lines().count(), 1000);
+
+        let temp_dir = tempdir().unwrap();
+        let test_path = temp_dir.path().join("test_input.txt");
+        std::fs::write(&test_path, test_input).unwrap();
+        assert!(part1(&test_path).is_ok());
+        assert!(part2(&test_path).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(parse_input("3   4\n4\n"), Err(Error::Parse(_))));
+        assert!(matches!(
+            parse_input("3   4\n4   x\n"),
+            Err(Error::Parse(_))
+        ));
+        assert_eq!(parse_input("").unwrap(), (vec![], vec![]));
+    }
 }
@@ -1,11 +1,119 @@
-use std::collections::HashSet;
+use crate::answer::{checked_sum, Answer};
+use crate::error::{Error, Result};
+use crate::options::{self, PuzzleOptions};
+use crate::parse::grid_lines;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::path::Path;
 
-fn get_trailheads(grid: &Vec<Vec<u32>>) -> Vec<(usize, usize)> {
+/// The heights a hiking trail climbs between and how it's allowed to step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The height every trail starts at
+    pub trailhead: u32,
+    /// The height every trail ends at
+    pub peak: u32,
+    /// How much the height changes with each step, which can be negative to walk down
+    pub step: i32,
+    /// 4 to only step up, down, left and right, or 8 to also step diagonally
+    pub directions: u32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            trailhead: 0,
+            peak: 9,
+            step: 1,
+            directions: 4,
+        }
+    }
+}
+
+impl PuzzleOptions for Options {
+    fn values(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("trailhead", self.trailhead.to_string()),
+            ("peak", self.peak.to_string()),
+            ("step", self.step.to_string()),
+            ("directions", self.directions.to_string()),
+        ]
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "trailhead" => self.trailhead = options::value(key, value)?,
+            "peak" => self.peak = options::value(key, value)?,
+            "step" => self.step = options::value(key, value)?,
+            "directions" => self.directions = options::value(key, value)?,
+            _ => return Err(options::unknown(key)),
+        }
+        Ok(())
+    }
+}
+
+/// The topographic map, where `None` is a tile that can't be stepped on
+pub type Map = Vec<Vec<Option<u32>>>;
+
+const CARDINAL_STEPS: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const ALL_STEPS: [(isize, isize); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+/// How a hike moves: the height at each step from trailhead to peak, and the directions it
+/// can step in
+struct Rules {
+    climb: Vec<u32>,
+    steps: &'static [(isize, isize)],
+}
+
+impl Rules {
+    fn new(options: &Options) -> Result<Self> {
+        let steps: &[(isize, isize)] = match options.directions {
+            4 => &CARDINAL_STEPS,
+            8 => &ALL_STEPS,
+            n => {
+                return Err(Error::Option(format!(
+                    "trails step in 4 or 8 directions, not {n}"
+                )))
+            }
+        };
+        if options.step == 0 {
+            return Err(Error::Option("step can't be 0".to_string()));
+        }
+
+        // Heights are single digits, and a peak that isn't a whole number of steps from the
+        // trailhead is never reached
+        let (trailhead, peak) = (i64::from(options.trailhead), i64::from(options.peak));
+        let step = i64::from(options.step);
+        let reachable = trailhead.max(peak) <= 9 && (peak - trailhead) % step == 0;
+        let climb = if reachable && (peak - trailhead) / step >= 0 {
+            (0..=(peak - trailhead) / step)
+                .filter_map(|i| u32::try_from(trailhead + i * step).ok())
+                .collect()
+        } else {
+            vec![]
+        };
+
+        Ok(Self { climb, steps })
+    }
+}
+
+fn get_trailheads(grid: &Map, rules: &Rules) -> Vec<(usize, usize)> {
+    let Some(&trailhead) = rules.climb.first() else {
+        return vec![];
+    };
     let mut trailheads = vec![];
-    for y in 0..grid.len() {
-        for x in 0..grid[y].len() {
-            if grid[y][x] == 0 {
+    for (y, row) in grid.iter().enumerate() {
+        for (x, &h) in row.iter().enumerate() {
+            if h == Some(trailhead) {
                 trailheads.push((x, y));
             }
         }
@@ -13,98 +121,252 @@ fn get_trailheads(grid: &Vec<Vec<u32>>) -> Vec<(usize, usize)> {
     trailheads
 }
 
-fn next_steps(grid: &Vec<Vec<u32>>, pos: (usize, usize)) -> Vec<(usize, usize)> {
-    let (x, y) = pos;
-    let height = grid[y][x];
-    let mut valid_steps = Vec::new();
+/// The neighbours of `pos` that are at `height`
+fn next_steps<'a>(
+    grid: &'a Map,
+    rules: &'a Rules,
+    pos: (usize, usize),
+    height: u32,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    rules.steps.iter().filter_map(move |&(dx, dy)| {
+        let (x, y) = (pos.0.checked_add_signed(dx)?, pos.1.checked_add_signed(dy)?);
+        (*grid.get(y)?.get(x)? == Some(height)).then_some((x, y))
+    })
+}
+
+/// The peaks reachable from each position and the number of hiking trails to them, found by
+/// sweeping down from the peaks since every trail from a position continues from a position
+/// one step further along the climb. The peaks reached are kept as a bitset, so the peaks are
+/// swept 64 at a time, only through the rows close enough to climb to them
+struct Trails {
+    width: usize,
+    num_peaks: Vec<u64>,
+    num_hikes: Vec<u64>,
+}
 
-    //try stepping in all four cardinal directions to see if the height is one-higher than current
-    if x >= 1 && grid[y][x - 1] == height + 1 {
-        valid_steps.push((x - 1, y))
+impl Trails {
+    fn new(grid: &Map, rules: &Rules) -> Result<Self> {
+        let (width, height) = (grid[0].len(), grid.len());
+
+        // Every position at each height of the climb, from the trailhead up, in reading order
+        let mut layers: Vec<Vec<(usize, usize)>> = vec![vec![]; rules.climb.len()];
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &h) in row.iter().enumerate() {
+                for (layer, &c) in layers.iter_mut().zip(&rules.climb) {
+                    if h == Some(c) {
+                        layer.push((x, y));
+                    }
+                }
+            }
+        }
+
+        let mut trails = Self {
+            width,
+            num_peaks: vec![0; width * height],
+            num_hikes: vec![0; width * height],
+        };
+        let Some(peaks) = layers.last() else {
+            return Ok(trails);
+        };
+
+        // Which of the current 64 peaks each position reaches, and by how many trails
+        let mut reached = vec![0_u64; width * height];
+        let mut hikes = vec![0_u64; width * height];
+
+        for chunk in peaks.chunks(64) {
+            // Each step moves at most one row, so only the rows within as many steps as are
+            // left in the climb can reach these peaks
+            let (first_peak_row, last_peak_row) = (chunk[0].1, chunk[chunk.len() - 1].1);
+            let in_rows = |k: usize, layer: &[(usize, usize)]| {
+                let steps_left = layers.len() - 1 - k;
+                let first_row = first_peak_row.saturating_sub(steps_left);
+                let start = layer.partition_point(|&(_, y)| y < first_row);
+                let end = layer.partition_point(|&(_, y)| y <= last_peak_row + steps_left);
+                start..end
+            };
+
+            // A peak reaches only itself, by the one trail that's already there
+            for (bit, &pos) in chunk.iter().enumerate() {
+                let i = trails.index(pos);
+                reached[i] = 1 << bit;
+                hikes[i] = 1;
+            }
+
+            for (k, positions) in layers.iter().enumerate().rev() {
+                for &pos in &positions[in_rows(k, positions)] {
+                    let i = trails.index(pos);
+                    if let Some(&next_height) = rules.climb.get(k + 1) {
+                        for next_pos in next_steps(grid, rules, pos, next_height) {
+                            let j = trails.index(next_pos);
+                            reached[i] |= reached[j];
+                            hikes[i] = hikes[i].checked_add(hikes[j]).ok_or(Error::Overflow)?;
+                        }
+                    }
+                    trails.num_peaks[i] += u64::from(reached[i].count_ones());
+                    trails.num_hikes[i] = trails.num_hikes[i]
+                        .checked_add(hikes[i])
+                        .ok_or(Error::Overflow)?;
+                }
+            }
+
+            for (k, positions) in layers.iter().enumerate() {
+                for &pos in &positions[in_rows(k, positions)] {
+                    let i = trails.index(pos);
+                    (reached[i], hikes[i]) = (0, 0);
+                }
+            }
+        }
+
+        Ok(trails)
     }
-    if x + 1 <= grid[0].len() - 1 && grid[y][x + 1] == height + 1 {
-        valid_steps.push((x + 1, y))
+
+    const fn index(&self, pos: (usize, usize)) -> usize {
+        pos.1 * self.width + pos.0
     }
-    if y >= 1 && grid[y - 1][x] == height + 1 {
-        valid_steps.push((x, y - 1))
+
+    /// How many peaks can be reached from `pos`
+    fn score(&self, pos: (usize, usize)) -> u64 {
+        self.num_peaks[self.index(pos)]
     }
-    if y + 1 <= grid.len() - 1 && grid[y + 1][x] == height + 1 {
-        valid_steps.push((x, y + 1))
+
+    /// How many distinct hiking trails lead from `pos` to a peak
+    fn rating(&self, pos: (usize, usize)) -> u64 {
+        self.num_hikes[self.index(pos)]
     }
 
-    valid_steps
-}
+    /// Follow every trail from `pos`, only stepping where a trail continues, until there are
+    /// `limit` of them
+    fn enumerate(
+        &self,
+        grid: &Map,
+        rules: &Rules,
+        trail: &mut Vec<(usize, usize)>,
+        limit: usize,
+        found: &mut Vec<Vec<(usize, usize)>>,
+    ) {
+        let Some(&pos) = trail.last() else {
+            return;
+        };
+        if found.len() >= limit || self.rating(pos) == 0 {
+            return;
+        }
 
-fn get_trail_tails(
-    grid: &Vec<Vec<u32>>,
-    pos: (usize, usize),
-    mut trail_tails: HashSet<(usize, usize)>,
-) -> HashSet<(usize, usize)> {
-    let height = grid[pos.1][pos.0];
-
-    if height == 9 {
-        // Basecase: we've found a trailhead!
-        trail_tails.insert(pos);
-    } else {
-        for next_pos in next_steps(&grid, pos) {
-            trail_tails.extend(get_trail_tails(&grid, next_pos, trail_tails.clone()));
+        let Some(&height) = rules.climb.get(trail.len()) else {
+            // Basecase: we've found a peak!
+            found.push(trail.clone());
+            return;
+        };
+        for next_pos in next_steps(grid, rules, pos, height) {
+            trail.push(next_pos);
+            self.enumerate(grid, rules, trail, limit, found);
+            trail.pop();
         }
     }
-    trail_tails
 }
 
-fn num_distinct_hikes(grid: &Vec<Vec<u32>>, pos: (usize, usize)) -> u32 {
-    let height = grid[pos.1][pos.0];
-
-    if height == 9 {
-        // Basecase: we've found a trailhead!
-        1
-    } else {
-        next_steps(&grid, pos)
-            .iter()
-            .map(|&next_pos| num_distinct_hikes(&grid, next_pos))
-            .sum()
-    }
+/// Read the topographic map of heights, one row per line, with `.` for impassable tiles
+pub fn parse_input(text: &str) -> Result<Map> {
+    const RADIX: u32 = 10;
+    grid_lines(text)?
+        .iter()
+        .enumerate()
+        .map(|(y, l)| {
+            l.chars()
+                .map(|c| match c {
+                    '.' => Some(None),
+                    _ => c.to_digit(RADIX).map(Some),
+                })
+                .collect::<Option<Vec<Option<u32>>>>()
+                .ok_or_else(|| Error::Parse(format!("line {}: expected heights 0-9 or .", y + 1)))
+        })
+        .collect()
 }
 
-pub fn part1(data_path: &Path) -> u32 {
-    // Create grid of u32 from input
-    let text = std::fs::read_to_string(data_path).unwrap();
-    const RADIX: u32 = 10;
-    let grid: Vec<Vec<u32>> = text
-        .lines()
-        .map(|l| l.chars().filter_map(|c| c.to_digit(RADIX)).collect())
-        .collect();
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    part1_with(data_path, &Options::default())
+}
 
-    // Get all the trailheads
-    let trailheads = get_trailheads(&grid);
+pub fn part1_with(data_path: &Path, options: &Options) -> Result<Answer> {
+    // Create grid of heights from input
+    let text = std::fs::read_to_string(data_path)?;
+    let grid = parse_input(&text)?;
 
     // Score each trailhead and return the sum
-    let scores: Vec<u32> = trailheads
-        .iter()
-        .map(|th| get_trail_tails(&grid, *th, HashSet::new()).len() as u32)
-        .collect();
-    scores.iter().sum()
+    let rules = Rules::new(options)?;
+    let trails = Trails::new(&grid, &rules)?;
+    let scores = get_trailheads(&grid, &rules)
+        .into_iter()
+        .map(|th| trails.score(th));
+    Ok(checked_sum(scores)?.into())
 }
 
-pub fn part2(data_path: &Path) -> u32 {
-    // Create grid of u32 from input
-    let text = std::fs::read_to_string(data_path).unwrap();
-    const RADIX: u32 = 10;
-    let grid: Vec<Vec<u32>> = text
-        .lines()
-        .map(|l| l.chars().filter_map(|c| c.to_digit(RADIX)).collect())
-        .collect();
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    part2_with(data_path, &Options::default())
+}
 
-    // Get all the trailheads
-    let trailheads = get_trailheads(&grid);
+pub fn part2_with(data_path: &Path, options: &Options) -> Result<Answer> {
+    // Create grid of heights from input
+    let text = std::fs::read_to_string(data_path)?;
+    let grid = parse_input(&text)?;
 
     // Score the number of distinct hikes
-    let scores: Vec<u32> = trailheads
-        .iter()
-        .map(|th| num_distinct_hikes(&grid, *th))
+    let rules = Rules::new(options)?;
+    let trails = Trails::new(&grid, &rules)?;
+    let ratings = get_trailheads(&grid, &rules)
+        .into_iter()
+        .map(|th| trails.rating(th));
+    Ok(checked_sum(ratings)?.into())
+}
+
+/// Up to `limit` hiking trails, as the (x, y) positions each one climbs through
+pub fn trails(
+    data_path: &Path,
+    options: &Options,
+    limit: usize,
+) -> Result<Vec<Vec<(usize, usize)>>> {
+    let text = std::fs::read_to_string(data_path)?;
+    let grid = parse_input(&text)?;
+
+    let rules = Rules::new(options)?;
+    let trails = Trails::new(&grid, &rules)?;
+    let mut found = vec![];
+    for th in get_trailheads(&grid, &rules) {
+        trails.enumerate(&grid, &rules, &mut vec![th], limit, &mut found);
+    }
+    Ok(found)
+}
+
+/// Generate a `size` by `size` topographic map with hiking trails carved into it
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut grid: Vec<Vec<u32>> = (0..size)
+        .map(|_| (0..size).map(|_| rng.gen_range(0..=9)).collect())
         .collect();
-    scores.iter().sum()
+
+    // Random heights rarely line up into trails, so walk some gradual climbs into the map
+    for _ in 0..size {
+        let (mut x, mut y) = (rng.gen_range(0..size), rng.gen_range(0..size));
+        for height in 0..=9 {
+            grid[y][x] = height;
+
+            let (dx, dy) = CARDINAL_STEPS[rng.gen_range(0..4)];
+            match (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
+                (Some(nx), Some(ny)) if nx < size && ny < size => (x, y) = (nx, ny),
+                _ => break,
+            }
+        }
+    }
+
+    grid.iter()
+        .map(|row| {
+            let row: String = row
+                .iter()
+                .map(|&h| char::from_digit(h, 10).unwrap())
+                .collect();
+            row + "\n"
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -138,7 +400,7 @@ mod tests {
 01329801
 10456732\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 36);
     }
 
@@ -154,7 +416,200 @@ mod tests {
 01329801
 10456732\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 81);
     }
+
+    #[test]
+    fn test_options() {
+        let test_input = "\
+0123
+1234
+8765
+9876\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        assert_eq!(part2(&test_path).unwrap(), 16);
+
+        // Stopping at the 4 leaves only the climbs through the top right corner
+        let options = Options {
+            peak: 4,
+            ..Options::default()
+        };
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 1);
+        assert_eq!(part2_with(&test_path, &options).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_trails() {
+        let test_input = "\
+0123
+1234
+8765
+9876\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let result = trails(&test_path, &Options::default(), 100).unwrap();
+        assert_eq!(result.len(), 16);
+        assert!(result.iter().all(|t| t.len() == 10 && t[0] == (0, 0)));
+        assert!(result.contains(&vec![
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (3, 0),
+            (3, 1),
+            (3, 2),
+            (2, 2),
+            (1, 2),
+            (0, 2),
+            (0, 3)
+        ]));
+
+        let result = trails(&test_path, &Options::default(), 5).unwrap();
+        assert_eq!(result.len(), 5);
+        let options = Options {
+            peak: 4,
+            ..Options::default()
+        };
+        let result = trails(&test_path, &options, 100).unwrap();
+        assert_eq!(result.len(), 4);
+    }
+
+    #[test]
+    fn test_impassable_tiles() {
+        let scores = [
+            (
+                "...0...\n...1...\n...2...\n6543456\n7.....7\n8.....8\n9.....9\n",
+                2,
+            ),
+            (
+                "..90..9\n...1.98\n...2..7\n6543456\n765.987\n876....\n987....\n",
+                4,
+            ),
+            (
+                "10..9..\n2...8..\n3...7..\n4567654\n...8..3\n...9..2\n.....01\n",
+                3,
+            ),
+        ];
+        for (test_input, score) in scores {
+            let (_d, _f, test_path) = create_test_file(test_input);
+            assert_eq!(part1(&test_path).unwrap(), score);
+        }
+
+        let ratings = [
+            (
+                ".....0.\n..4321.\n..5..2.\n..6543.\n..7..4.\n..8765.\n..9....\n",
+                3,
+            ),
+            (
+                "..90..9\n...1.98\n...2..7\n6543456\n765.987\n876....\n987....\n",
+                13,
+            ),
+            ("012345\n123456\n234567\n345678\n4.6789\n56789.\n", 227),
+        ];
+        for (test_input, rating) in ratings {
+            let (_d, _f, test_path) = create_test_file(test_input);
+            assert_eq!(part2(&test_path).unwrap(), rating);
+        }
+    }
+
+    #[test]
+    fn test_hiking_rules() {
+        let test_input = "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+
+        // Walking down from the peaks finds the same trails
+        let options = Options {
+            trailhead: 9,
+            peak: 0,
+            step: -1,
+            ..Options::default()
+        };
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 36);
+        assert_eq!(part2_with(&test_path, &options).unwrap(), 81);
+
+        let test_input = "02468\n1.3.5\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let options = Options {
+            peak: 8,
+            step: 2,
+            ..Options::default()
+        };
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 1);
+        let options = Options {
+            peak: 3,
+            ..Options::default()
+        };
+        assert_eq!(part2_with(&test_path, &options).unwrap(), 0);
+        let options = Options {
+            directions: 8,
+            ..options
+        };
+        assert_eq!(part2_with(&test_path, &options).unwrap(), 1);
+
+        // A peak that can't be reached in whole steps has no trails
+        let options = Options {
+            peak: 7,
+            step: 2,
+            ..Options::default()
+        };
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 0);
+        let options = Options {
+            step: 0,
+            ..Options::default()
+        };
+        assert!(matches!(
+            part1_with(&test_path, &options),
+            Err(Error::Option(_))
+        ));
+        let options = Options {
+            directions: 6,
+            ..Options::default()
+        };
+        assert!(matches!(
+            part2_with(&test_path, &options),
+            Err(Error::Option(_))
+        ));
+    }
+
+    #[test]
+    fn test_many_peaks() {
+        // 80 peaks take more than one sweep of 64
+        let test_input = "01234567899876543210".repeat(40) + "\n";
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert_eq!(part1(&test_path).unwrap(), 80);
+        assert_eq!(part2(&test_path).unwrap(), 80);
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 40);
+        assert_eq!(test_input, generate(2024, 40));
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert!(part1(&test_path).unwrap() != 0);
+        assert!(part2(&test_path).unwrap() != 0);
+
+        // Big enough that keeping the peaks reachable from every position wouldn't fit in memory
+        let test_input = generate(2024, 1000);
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert!(part1(&test_path).unwrap() != 0);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert_eq!(
+            parse_input("01\n98\n").unwrap(),
+            vec![vec![Some(0), Some(1)], vec![Some(9), Some(8)]]
+        );
+        assert_eq!(parse_input("0.\n").unwrap(), vec![vec![Some(0), None]]);
+        assert!(matches!(parse_input("0123\n12a4\n"), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("0123\n123\n"), Err(Error::Parse(_))));
+    }
 }
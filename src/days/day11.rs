@@ -1,66 +1,303 @@
-use cached::proc_macro::cached;
+use crate::answer::Answer;
+use crate::error::{Error, Result};
+use crate::options::{self, PuzzleOptions};
+use crate::parse::number;
+use num_bigint::BigUint;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-fn next_step(stone: usize) -> Vec<usize> {
-    if stone == 0 {
-        return vec![1];
+/// How many stones' next steps are remembered unless told otherwise
+pub const DEFAULT_MEMO_CAPACITY: usize = 100_000;
+
+/// How many times to blink in each part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub part1_blinks: u32,
+    pub part2_blinks: u32,
+    /// The most stones to remember the next step of
+    pub memo_capacity: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            part1_blinks: 25,
+            part2_blinks: 75,
+            memo_capacity: DEFAULT_MEMO_CAPACITY,
+        }
     }
+}
 
-    let num_digits = stone.ilog10() as usize;
+impl PuzzleOptions for Options {
+    fn values(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("part1_blinks", self.part1_blinks.to_string()),
+            ("part2_blinks", self.part2_blinks.to_string()),
+            ("memo_capacity", self.memo_capacity.to_string()),
+        ]
+    }
 
-    if num_digits % 2 == 1 {
-        let str_digits = stone.to_string();
-        let p1: usize = str_digits[..num_digits / 2 + 1].parse().unwrap();
-        let p2: usize = str_digits[num_digits / 2 + 1..].parse().unwrap();
-        vec![p1, p2]
-    } else {
-        vec![stone * 2024]
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "part1_blinks" => self.part1_blinks = options::value(key, value)?,
+            "part2_blinks" => self.part2_blinks = options::value(key, value)?,
+            "memo_capacity" => self.memo_capacity = options::value(key, value)?,
+            _ => return Err(options::unknown(key)),
+        }
+        Ok(())
     }
 }
 
-#[cached]
-fn num_ending_stones(stone: usize, num_steps: u8) -> usize {
-    if num_steps == 0 {
-        // Base case, on the last step
-        1
-    } else {
-        // Recursive case
-        next_step(stone)
-            .iter()
-            .map(|&s| num_ending_stones(s, num_steps - 1))
-            .sum()
+/// What happens to a stone each time you blink
+pub trait Rules {
+    fn next_step(&self, stone: u64) -> Result<Vec<u64>>;
+}
+
+/// The puzzle's rules: 0 becomes 1, even numbers of digits split in half, otherwise times 2024
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PuzzleRules;
+
+impl Rules for PuzzleRules {
+    fn next_step(&self, stone: u64) -> Result<Vec<u64>> {
+        if stone == 0 {
+            return Ok(vec![1]);
+        }
+
+        let num_digits = stone.ilog10() as usize;
+
+        if num_digits % 2 == 1 {
+            let str_digits = stone.to_string();
+            let p1: u64 = str_digits[..=num_digits / 2].parse().unwrap();
+            let p2: u64 = str_digits[num_digits / 2 + 1..].parse().unwrap();
+            Ok(vec![p1, p2])
+        } else {
+            Ok(vec![stone.checked_mul(2024).ok_or(Error::Overflow)?])
+        }
     }
 }
 
-pub fn part1(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+impl<R: Rules + ?Sized> Rules for &R {
+    fn next_step(&self, stone: u64) -> Result<Vec<u64>> {
+        (**self).next_step(stone)
+    }
+}
 
-    let mut stones: Vec<usize> = text
-        .split_whitespace()
-        .filter_map(|n| n.parse().ok())
-        .collect();
+/// Some rules along with the next step of up to `capacity` of the stones they've been asked
+/// about, so those stones are only worked out once however many times they turn up
+///
+/// Once it's full, any other stone is worked out again every time
+#[derive(Debug, Clone)]
+pub struct Memo<R> {
+    rules: R,
+    capacity: usize,
+    next_steps: HashMap<u64, Vec<u64>>,
+}
 
-    for _ in 0..25 {
-        stones = stones.iter().flat_map(|&s| next_step(s)).collect();
+impl<R: Rules> Memo<R> {
+    pub fn new(rules: R, capacity: usize) -> Self {
+        Self {
+            rules,
+            capacity,
+            next_steps: HashMap::new(),
+        }
     }
 
-    stones.len().try_into().unwrap()
+    /// What `stone` changes into, from the memo if it's been seen before
+    pub fn next_step(&mut self, stone: u64) -> Result<Cow<'_, [u64]>> {
+        if self.next_steps.len() < self.capacity && !self.next_steps.contains_key(&stone) {
+            let next = self.rules.next_step(stone)?;
+            self.next_steps.insert(stone, next);
+        }
+        match self.next_steps.get(&stone) {
+            Some(next) => Ok(Cow::Borrowed(next)),
+            None => Ok(Cow::Owned(self.rules.next_step(stone)?)),
+        }
+    }
+
+    /// The most stones that will be remembered
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// How many stones have had their next step remembered
+    pub fn len(&self) -> usize {
+        self.next_steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next_steps.is_empty()
+    }
+
+    /// Forget the next step of every stone, e.g. to free the memory after a long run
+    pub fn clear(&mut self) {
+        self.next_steps.clear();
+    }
+}
+
+/// How many of each stone there are, which is all that matters since stones never interact
+pub type StoneCounts = HashMap<u64, BigUint>;
+
+/// Blink once, moving each stone's count to the stones it changes into
+pub fn blink(memo: &mut Memo<impl Rules>, counts: &StoneCounts) -> Result<StoneCounts> {
+    let mut next_counts = StoneCounts::new();
+    for (&stone, count) in counts {
+        for &next_stone in memo.next_step(stone)?.iter() {
+            *next_counts.entry(next_stone).or_default() += count;
+        }
+    }
+    Ok(next_counts)
+}
+
+/// The stones after blinking some number of times
+#[pyclass(get_all, frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoneDistribution {
+    /// How many of each stone there are
+    pub counts: StoneCounts,
+    pub distinct: usize,
+    /// The first blink that only gave stones that had been seen before, after which no new
+    /// stones ever appear
+    pub stops_growing: Option<u32>,
+}
+
+/// How many of each stone there are after blinking `num_blinks` times, and when new stones
+/// stop appearing
+pub fn distribution(
+    memo: &mut Memo<impl Rules>,
+    stones: &[u64],
+    num_blinks: u32,
+) -> Result<StoneDistribution> {
+    let mut counts = StoneCounts::new();
+    for &stone in stones {
+        *counts.entry(stone).or_default() += 1_u32;
+    }
+
+    let mut seen: HashSet<u64> = counts.keys().copied().collect();
+    let mut stops_growing = None;
+    for i in 1..=num_blinks {
+        counts = blink(memo, &counts)?;
+
+        // Every stone seen so far changes into stones seen so far, so there's nothing new to find
+        let num_seen = seen.len();
+        seen.extend(counts.keys());
+        if stops_growing.is_none() && seen.len() == num_seen {
+            stops_growing = Some(i);
+        }
+    }
+
+    Ok(StoneDistribution {
+        distinct: counts.len(),
+        counts,
+        stops_growing,
+    })
+}
+
+/// How many of each stone there are after blinking `num_blinks` times
+pub fn count_stones(rules: &impl Rules, stones: &[u64], num_blinks: u32) -> Result<StoneCounts> {
+    let mut memo = Memo::new(rules, DEFAULT_MEMO_CAPACITY);
+    Ok(distribution(&mut memo, stones, num_blinks)?.counts)
+}
+
+/// The puzzle's stones after blinking `num_blinks` times
+pub fn stone_distribution(data_path: &Path, num_blinks: u32) -> Result<StoneDistribution> {
+    let text = std::fs::read_to_string(data_path)?;
+
+    let stones = parse_input(&text)?;
+    distribution(
+        &mut Memo::new(PuzzleRules, DEFAULT_MEMO_CAPACITY),
+        &stones,
+        num_blinks,
+    )
+}
+
+/// The puzzle's rules with a memo of up to `capacity` stones that's kept between calls, so
+/// Python can reuse it, see how big it's grown and clear it
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct StoneCache {
+    memo: Memo<PuzzleRules>,
+}
+
+#[pymethods]
+impl StoneCache {
+    #[new]
+    #[pyo3(signature = (capacity = DEFAULT_MEMO_CAPACITY))]
+    fn py_new(capacity: usize) -> Self {
+        Self {
+            memo: Memo::new(PuzzleRules, capacity),
+        }
+    }
+
+    #[getter]
+    pub const fn capacity(&self) -> usize {
+        self.memo.capacity()
+    }
+
+    /// The puzzle's stones after blinking `num_blinks` times
+    pub fn distribution(&mut self, data_path: &str, num_blinks: u32) -> Result<StoneDistribution> {
+        let text = std::fs::read_to_string(data_path)?;
+
+        let stones = parse_input(&text)?;
+        distribution(&mut self.memo, &stones, num_blinks)
+    }
+
+    /// How many stones have had their next step remembered
+    pub fn __len__(&self) -> usize {
+        self.memo.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.memo.clear();
+    }
 }
 
-pub fn part2(data_path: &Path) -> u64 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+fn num_ending_stones(data_path: &Path, num_blinks: u32, memo_capacity: usize) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    let stones: Vec<usize> = text
-        .split_whitespace()
-        .filter_map(|n| n.parse().ok())
+    let stones = parse_input(&text)?;
+    let mut memo = Memo::new(PuzzleRules, memo_capacity);
+    let counts = distribution(&mut memo, &stones, num_blinks)?.counts;
+
+    Ok(counts.into_values().sum::<BigUint>().into())
+}
+
+/// Read the numbers engraved on the line of stones
+pub fn parse_input(text: &str) -> Result<Vec<u64>> {
+    text.lines()
+        .enumerate()
+        .flat_map(|(i, l)| l.split_whitespace().map(move |n| number(n, i)))
+        .collect()
+}
+
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    part1_with(data_path, &Options::default())
+}
+
+pub fn part1_with(data_path: &Path, options: &Options) -> Result<Answer> {
+    num_ending_stones(data_path, options.part1_blinks, options.memo_capacity)
+}
+
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    part2_with(data_path, &Options::default())
+}
+
+pub fn part2_with(data_path: &Path, options: &Options) -> Result<Answer> {
+    num_ending_stones(data_path, options.part2_blinks, options.memo_capacity)
+}
+
+/// Generate a line of `size` engraved stones
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let stones: Vec<String> = (0..size)
+        .map(|_| rng.gen_range(0..1_000_000_u64).to_string())
         .collect();
 
-    stones
-        .iter()
-        .map(|&s| num_ending_stones(s, 75))
-        .sum::<usize>()
-        .try_into()
-        .unwrap()
+    stones.join(" ") + "\n"
 }
 
 #[cfg(test)]
@@ -86,7 +323,7 @@ mod tests {
     fn test_part1() {
         let test_input = "125 17\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 55312);
     }
 
@@ -94,7 +331,147 @@ mod tests {
     fn test_part2() {
         let test_input = "125 17\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 65601038650482);
     }
+
+    #[test]
+    fn test_part1_overflow() {
+        // An odd number of digits gets multiplied by 2024, which can't fit in a u64
+        let test_input = "10000000000000000\n";
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        let result = part1(&test_path);
+        assert!(matches!(result, Err(Error::Overflow)));
+    }
+
+    #[test]
+    fn test_options() {
+        let test_input = "125 17\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let options = Options {
+            part1_blinks: 6,
+            part2_blinks: 6,
+            ..Options::default()
+        };
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 22);
+        assert_eq!(part2_with(&test_path, &options).unwrap(), 22);
+    }
+
+    #[test]
+    fn test_memo_capacity() {
+        let test_input = "125 17\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let path = test_path.to_str().unwrap();
+        let expected = stone_distribution(&test_path, 75).unwrap();
+
+        // A full memo stops remembering stones but still gives the same stones
+        let mut cache = StoneCache::py_new(50);
+        for num_blinks in [5, 75, 100] {
+            cache.distribution(path, num_blinks).unwrap();
+            assert!(cache.__len__() <= 50);
+        }
+        assert_eq!(cache.__len__(), 50);
+        assert_eq!(cache.distribution(path, 75).unwrap(), expected);
+        cache.clear();
+        assert_eq!(cache.__len__(), 0);
+
+        let options = Options {
+            memo_capacity: 0,
+            ..Options::default()
+        };
+        assert_eq!(
+            part2_with(&test_path, &options).unwrap(),
+            65_601_038_650_482_u64
+        );
+    }
+
+    #[test]
+    fn test_many_blinks() {
+        let test_input = "125 17\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let options = Options {
+            part1_blinks: 500,
+            ..Options::default()
+        };
+        let Answer::Big(count) = part1_with(&test_path, &options).unwrap() else {
+            panic!("expected more stones than fit in a u64");
+        };
+        assert!(count.bits() > 128);
+    }
+
+    #[test]
+    fn test_distribution() {
+        let test_input = "125 17\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+
+        // 2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2
+        let result = stone_distribution(&test_path, 6).unwrap();
+        assert_eq!(result.distinct, 15);
+        assert_eq!(result.counts[&2], BigUint::from(4_u32));
+        assert_eq!(result.counts[&48], BigUint::from(2_u32));
+        assert_eq!(result.stops_growing, None);
+
+        // The puzzle's rules only ever make a few thousand different stones
+        let result = stone_distribution(&test_path, 100).unwrap();
+        let Some(stops_growing) = result.stops_growing else {
+            panic!("expected the stones to stop growing");
+        };
+        let stones_after = |n| {
+            let result = stone_distribution(&test_path, n).unwrap();
+            result.counts.into_keys().collect::<HashSet<u64>>()
+        };
+        let seen: HashSet<u64> = (0..=stops_growing).flat_map(stones_after).collect();
+        assert!(stones_after(stops_growing + 10).is_subset(&seen));
+    }
+
+    #[test]
+    fn test_other_rules() {
+        // Every stone splits into itself and the next stone, so the count doubles each blink
+        struct Doubling;
+        impl Rules for Doubling {
+            fn next_step(&self, stone: u64) -> Result<Vec<u64>> {
+                Ok(vec![stone, stone + 1])
+            }
+        }
+
+        let counts = count_stones(&Doubling, &[0], 3).unwrap();
+        let count = |stone| counts[&stone].clone();
+        assert_eq!(counts.len(), 4);
+        assert_eq!(count(0) + count(3), BigUint::from(2_u32));
+        assert_eq!(count(1), BigUint::from(3_u32));
+        assert_eq!(count(2), BigUint::from(3_u32));
+
+        // The memo works out each stone once, whichever rules it's given
+        let mut memo = Memo::new(Doubling, 10);
+        let result = distribution(&mut memo, &[0], 3).unwrap();
+        assert_eq!(result.counts, counts);
+        assert_eq!(memo.len(), 3);
+        memo.clear();
+        assert!(memo.is_empty());
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 8);
+        assert_eq!(test_input, generate(2024, 8));
+        assert_eq!(test_input.split_whitespace().count(), 8);
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert!(part1(&test_path).is_ok());
+        assert!(part2(&test_path).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(parse_input("125 17 -1"), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("125 seventeen"), Err(Error::Parse(_))));
+        assert_eq!(parse_input("125 17\n").unwrap(), vec![125, 17]);
+
+        // Stones can be spread over lines, and a bad one is reported on its own line
+        assert_eq!(parse_input("125\n17 3\n").unwrap(), vec![125, 17, 3]);
+        let Err(Error::Parse(reason)) = parse_input("125 17\n3 x\n") else {
+            panic!("expected x not to be a stone");
+        };
+        assert!(reason.starts_with("line 2"), "{reason}");
+    }
 }
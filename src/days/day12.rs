@@ -1,175 +1,347 @@
+use crate::answer::{checked_sum, to_u64, Answer};
+use crate::error::{Error, Result};
+use crate::parse::char_grid;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::HashSet;
 use std::path::Path;
 
-fn next_steps(grid: &Vec<Vec<char>>, pos: (usize, usize)) -> Vec<(usize, usize)> {
-    let (x, y) = pos;
-    let plant = grid[y][x];
-    let mut valid_steps = Vec::new();
-
-    //try stepping in all four cardinal directions to see if the plot is of the same plant type
-    if x >= 1 && grid[y][x - 1] == plant {
-        valid_steps.push((x - 1, y))
-    }
-    if x + 1 <= grid[0].len() - 1 && grid[y][x + 1] == plant {
-        valid_steps.push((x + 1, y))
-    }
-    if y >= 1 && grid[y - 1][x] == plant {
-        valid_steps.push((x, y - 1))
-    }
-    if y + 1 <= grid.len() - 1 && grid[y + 1][x] == plant {
-        valid_steps.push((x, y + 1))
-    }
+/// A contiguous region of a single plant type
+#[pyclass(get_all, frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub plant: char,
+    pub area: u64,
+    pub perimeter: u64,
+    pub sides: u64,
+    /// The top left and bottom right plots, as (x, y)
+    pub bounding_box: ((usize, usize), (usize, usize)),
+    /// The regions inside each hole in this region, as indices into the garden's regions, or
+    /// `None` if they weren't looked for since it's too slow to do for every garden that's priced
+    pub holes: Option<Vec<Vec<usize>>>,
+}
 
-    valid_steps
+/// All of the garden's regions and the total fence price
+#[pyclass(get_all, frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GardenReport {
+    pub regions: Vec<Region>,
+    pub total: u64,
 }
 
-fn discover_region(grid: &mut Vec<Vec<char>>, pos: (usize, usize)) -> HashSet<(usize, usize)> {
-    let mut visited = HashSet::new();
-    let mut to_visit = vec![pos];
+impl GardenReport {
+    fn new(regions: Vec<Region>, fences: impl Fn(&Region) -> u64) -> Result<Self> {
+        let prices = regions
+            .iter()
+            .map(|r| r.area.checked_mul(fences(r)).ok_or(Error::Overflow))
+            .collect::<Result<Vec<u64>>>()?;
+        let total = checked_sum(prices)?;
+        Ok(Self { regions, total })
+    }
+}
 
-    while let Some((x, y)) = to_visit.pop() {
-        // This plot was already visited, skip
-        if grid[y][x] == '.' {
-            continue;
-        }
+/// Give every plot the id of its region, numbering regions in the order they're first found
+fn label_regions(grid: &[Vec<char>]) -> (Vec<Vec<usize>>, usize) {
+    let mut region_ids = vec![vec![usize::MAX; grid[0].len()]; grid.len()];
+    let mut num_regions = 0;
 
-        // Add the next steps to the stack
-        to_visit.extend(next_steps(&grid, (x, y)));
+    for y in 0..grid.len() {
+        for x in 0..grid[y].len() {
+            // Plot already part of region, skip
+            if region_ids[y][x] != usize::MAX {
+                continue;
+            }
 
-        // Mark this plot as visited
-        visited.insert((x, y));
-        grid[y][x] = '.';
+            // Flood the region with its id, crossing only to plots of the same plant
+            region_ids[y][x] = num_regions;
+            let mut to_visit = vec![(x, y)];
+            while let Some(pos) = to_visit.pop() {
+                for (nx, ny) in neighbours(grid, pos) {
+                    if region_ids[ny][nx] == usize::MAX && grid[ny][nx] == grid[y][x] {
+                        region_ids[ny][nx] = num_regions;
+                        to_visit.push((nx, ny));
+                    }
+                }
+            }
+            num_regions += 1;
+        }
     }
 
-    visited
+    (region_ids, num_regions)
 }
 
-fn get_fence_segments(
-    plots: &HashSet<(usize, usize)>,
-) -> (Vec<((i32, i32), (i32, i32))>, Vec<((i32, i32), (i32, i32))>) {
-    let mut vert_fence_segments = Vec::new();
-    let mut horz_fence_segments = Vec::new();
-
-    for &(x, y) in plots {
-        let lt_same_plant = x >= 1 && plots.contains(&(x - 1, y));
-        let rt_same_plant = plots.contains(&(x + 1, y));
-        let up_same_plant = y >= 1 && plots.contains(&(x, y - 1));
-        let dn_same_plant = plots.contains(&(x, y + 1));
-
-        let x: i32 = x as i32;
-        let y: i32 = y as i32;
+/// The plots next to `pos` in the four cardinal directions that are inside the garden
+fn neighbours(grid: &[Vec<char>], pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+    let (x, y) = pos;
+    let (width, height) = (grid[0].len(), grid.len());
+    [(0, -1), (1, 0), (0, 1), (-1, 0)]
+        .into_iter()
+        .filter_map(move |(dx, dy)| {
+            let nx = x.checked_add_signed(dx).filter(|&nx| nx < width)?;
+            let ny = y.checked_add_signed(dy).filter(|&ny| ny < height)?;
+            Some((nx, ny))
+        })
+}
 
-        if !lt_same_plant {
-            vert_fence_segments.push(((x, y), (x - 1, y)));
+/// Count the fences and corners around each plot, where a region has as many sides as corners
+fn measure_regions(
+    grid: &[Vec<char>],
+    region_ids: &[Vec<usize>],
+    num_regions: usize,
+) -> Result<Vec<Region>> {
+    let mut plants = vec![' '; num_regions];
+    let mut areas = vec![0; num_regions];
+    let mut perimeters = vec![0; num_regions];
+    let mut corners = vec![0; num_regions];
+    let mut bounding_boxes = vec![((usize::MAX, usize::MAX), (0, 0)); num_regions];
+
+    for (y, row) in region_ids.iter().enumerate() {
+        for (x, &id) in row.iter().enumerate() {
+            // Whether the plot dx, dy away is in the same region (outside the garden never is)
+            let same = |dx: isize, dy: isize| {
+                let nx = x.checked_add_signed(dx);
+                let ny = y.checked_add_signed(dy);
+                nx.zip(ny)
+                    .and_then(|(nx, ny)| region_ids.get(ny)?.get(nx))
+                    .is_some_and(|&n| n == id)
+            };
+
+            plants[id] = grid[y][x];
+            areas[id] += 1;
+            let ((min_x, min_y), (max_x, max_y)) = &mut bounding_boxes[id];
+            (*min_x, *min_y) = (x.min(*min_x), y.min(*min_y));
+            (*max_x, *max_y) = (x.max(*max_x), y.max(*max_y));
+            perimeters[id] += [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .iter()
+                .filter(|&&(dx, dy)| !same(dx, dy))
+                .count();
+
+            // A corner is either outside (both sides fenced) or inside (neither side fenced,
+            // but the diagonal is another region)
+            for (dx, dy) in [(-1, -1), (1, -1), (1, 1), (-1, 1)] {
+                let (side_x, side_y) = (same(dx, 0), same(0, dy));
+                if (!side_x && !side_y) || (side_x && side_y && !same(dx, dy)) {
+                    corners[id] += 1;
+                }
+            }
         }
+    }
 
-        if !rt_same_plant {
-            vert_fence_segments.push(((x, y), (x + 1, y)));
-        }
+    (0..num_regions)
+        .map(|id| {
+            Ok(Region {
+                plant: plants[id],
+                area: to_u64(areas[id])?,
+                perimeter: to_u64(perimeters[id])?,
+                sides: to_u64(corners[id])?,
+                bounding_box: bounding_boxes[id],
+                holes: None,
+            })
+        })
+        .collect()
+}
 
-        if !up_same_plant {
-            horz_fence_segments.push(((x, y), (x, y - 1)));
+/// The other regions inside each hole of region `id`, i.e. each group of plots that can't
+/// reach the edge of the region's bounding box without crossing the region
+fn find_holes(
+    region_ids: &[Vec<usize>],
+    id: usize,
+    bounding_box: ((usize, usize), (usize, usize)),
+) -> Vec<Vec<usize>> {
+    let ((min_x, min_y), (max_x, max_y)) = bounding_box;
+    let mut visited = vec![vec![false; max_x - min_x + 1]; max_y - min_y + 1];
+    let mut holes = vec![];
+
+    // Plots on the edge of the bounding box are outside the region, so start with those
+    let mut starts: Vec<(usize, usize)> = (min_y..=max_y)
+        .flat_map(|y| (min_x..=max_x).map(move |x| (x, y)))
+        .collect();
+    starts.sort_by_key(|&(x, y)| !(x == min_x || x == max_x || y == min_y || y == max_y));
+
+    for (x, y) in starts {
+        if region_ids[y][x] == id || visited[y - min_y][x - min_x] {
+            continue;
+        }
+        let on_edge = x == min_x || x == max_x || y == min_y || y == max_y;
+
+        // Flood the plots that aren't part of the region, staying inside the bounding box
+        let mut enclosed = HashSet::new();
+        visited[y - min_y][x - min_x] = true;
+        let mut to_visit = vec![(x, y)];
+        while let Some((x, y)) = to_visit.pop() {
+            enclosed.insert(region_ids[y][x]);
+            for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+                else {
+                    continue;
+                };
+                if (min_x..=max_x).contains(&nx)
+                    && (min_y..=max_y).contains(&ny)
+                    && region_ids[ny][nx] != id
+                    && !visited[ny - min_y][nx - min_x]
+                {
+                    visited[ny - min_y][nx - min_x] = true;
+                    to_visit.push((nx, ny));
+                }
+            }
         }
 
-        if !dn_same_plant {
-            horz_fence_segments.push(((x, y), (x, y + 1)));
+        if !on_edge {
+            let mut enclosed: Vec<usize> = enclosed.into_iter().collect();
+            enclosed.sort_unstable();
+            holes.push(enclosed);
         }
     }
 
-    (vert_fence_segments, horz_fence_segments)
+    holes
 }
 
-fn count_fences(fence_segments: &mut Vec<((i32, i32), (i32, i32))>, vertical: bool) -> usize {
-    let mut num_fences = 0;
-    while let Some(start_segment) = fence_segments.pop() {
-        num_fences += 1;
-
-        // Try and consume all the connected fence pieces to the start_segment
-        // slow with how I'm using remove, could likely make "fence numbers" instead
-        let mut segments_to_check = vec![start_segment];
-        while let Some(((x1, y1), (x2, y2))) = segments_to_check.pop() {
-            if vertical {
-                let up_segment = ((x1, y1 - 1), (x2, y2 - 1));
-                let dw_segment = ((x1, y1 + 1), (x2, y2 + 1));
-
-                if let Some(ind) = fence_segments.iter().position(|&x| x == up_segment) {
-                    segments_to_check.push(up_segment);
-                    fence_segments.remove(ind);
-                }
-
-                if let Some(ind) = fence_segments.iter().position(|&x| x == dw_segment) {
-                    segments_to_check.push(dw_segment);
-                    fence_segments.remove(ind);
-                }
-            } else {
-                let lt_segment = ((x1 - 1, y1), (x2 - 1, y2));
-                let rt_segment = ((x1 + 1, y1), (x2 + 1, y2));
-
-                if let Some(ind) = fence_segments.iter().position(|&x| x == lt_segment) {
-                    segments_to_check.push(lt_segment);
-                    fence_segments.remove(ind);
-                }
+/// Draw the garden with a fence between every two plots of different regions, using
+/// box-drawing characters, e.g. for a single `A` plot
+/// ```text
+/// ┌─┐
+/// │A│
+/// └─┘
+/// ```
+fn render_fences(grid: &[Vec<char>], region_ids: &[Vec<usize>]) -> String {
+    let (width, height) = (grid[0].len(), grid.len());
+
+    // Whether there's a fence on the left of plot x, y or above it, where the plots on either
+    // side of the fence are in different regions (or one is outside the garden)
+    let region = |x: Option<usize>, y: Option<usize>| {
+        x.zip(y)
+            .and_then(|(x, y)| region_ids.get(y)?.get(x))
+            .copied()
+    };
+    let fence_left = |x: usize, y: usize| {
+        y < height && region(x.checked_sub(1), Some(y)) != region(Some(x), Some(y))
+    };
+    let fence_above = |x: usize, y: usize| {
+        x < width && region(Some(x), y.checked_sub(1)) != region(Some(x), Some(y))
+    };
+
+    let mut lines = vec![];
+    for y in 0..=height {
+        // The fences above this row of plots, joined at the posts between them
+        let mut fences = String::new();
+        for x in 0..=width {
+            let up = y > 0 && fence_left(x, y - 1);
+            let right = fence_above(x, y);
+            let down = fence_left(x, y);
+            let left = x > 0 && fence_above(x - 1, y);
+            fences.push(match (up, right, down, left) {
+                (false, false, false, false) => ' ',
+                (_, false, _, false) => '│',
+                (false, _, false, _) => '─',
+                (false, true, true, false) => '┌',
+                (false, false, true, true) => '┐',
+                (true, true, false, false) => '└',
+                (true, false, false, true) => '┘',
+                (true, true, true, false) => '├',
+                (true, false, true, true) => '┤',
+                (false, true, true, true) => '┬',
+                (true, true, false, true) => '┴',
+                (true, true, true, true) => '┼',
+            });
+            if x < width {
+                fences.push(if fence_above(x, y) { '─' } else { ' ' });
+            }
+        }
+        lines.push(fences);
 
-                if let Some(ind) = fence_segments.iter().position(|&x| x == rt_segment) {
-                    segments_to_check.push(rt_segment);
-                    fence_segments.remove(ind);
-                }
+        if let Some(row) = grid.get(y) {
+            let mut plots = String::new();
+            for (x, &plant) in row.iter().enumerate() {
+                plots.push(if fence_left(x, y) { '│' } else { ' ' });
+                plots.push(plant);
             }
+            plots.push(if fence_left(width, y) { '│' } else { ' ' });
+            lines.push(plots);
         }
     }
-    num_fences
+
+    lines.join("\n") + "\n"
 }
 
-pub fn part1(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+/// Read the garden map of plants, one row per line
+pub fn parse_input(text: &str) -> Result<Vec<Vec<char>>> {
+    char_grid(text)
+}
 
-    let mut grid: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+fn find_regions(text: &str) -> Result<Vec<Region>> {
+    let grid = parse_input(text)?;
 
-    let mut score = 0;
+    let (region_ids, num_regions) = label_regions(&grid);
+    measure_regions(&grid, &region_ids, num_regions)
+}
 
-    // Look through the whole grid to find the regions and score them
-    for y in 0..grid.len() {
-        for x in 0..grid[y].len() {
-            // Plot already part of region, skip
-            if grid[y][x] == '.' {
-                continue;
-            }
+/// Every region of the garden and its holes, in the order they're found reading the map
+pub fn regions(data_path: &Path) -> Result<Vec<Region>> {
+    let text = std::fs::read_to_string(data_path)?;
+    let grid = parse_input(&text)?;
 
-            let plots = discover_region(&mut grid, (x, y));
-            let (vert_fence_segments, horz_fence_segments) = get_fence_segments(&plots);
-            score += (vert_fence_segments.len() + horz_fence_segments.len()) * plots.len()
-        }
+    let (region_ids, num_regions) = label_regions(&grid);
+    let mut regions = measure_regions(&grid, &region_ids, num_regions)?;
+    for (id, region) in regions.iter_mut().enumerate() {
+        region.holes = Some(find_holes(&region_ids, id, region.bounding_box));
     }
+    Ok(regions)
+}
 
-    score.try_into().unwrap()
+/// Draw the garden with its fences, see `render_fences`
+pub fn render(data_path: &Path) -> Result<String> {
+    let text = std::fs::read_to_string(data_path)?;
+    let grid = parse_input(&text)?;
+
+    let (region_ids, _) = label_regions(&grid);
+    Ok(render_fences(&grid, &region_ids))
 }
 
-pub fn part2(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    Ok(part1_detailed(data_path)?.total.into())
+}
 
-    let orig_grid: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
-    let mut grid = orig_grid.clone();
+pub fn part1_detailed(data_path: &Path) -> Result<GardenReport> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    let mut score = 0;
+    GardenReport::new(find_regions(&text)?, |r| r.perimeter)
+}
 
-    // Look through the whole grid to find the regions and score them
-    for y in 0..grid.len() {
-        for x in 0..grid[y].len() {
-            // Plot already part of region, skip
-            if grid[y][x] == '.' {
-                continue;
-            }
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    Ok(part2_detailed(data_path)?.total.into())
+}
+
+pub fn part2_detailed(data_path: &Path) -> Result<GardenReport> {
+    let text = std::fs::read_to_string(data_path)?;
 
-            let plots = discover_region(&mut grid, (x, y));
-            let (mut vert_fence_segments, mut horz_fence_segments) = get_fence_segments(&plots);
+    GardenReport::new(find_regions(&text)?, |r| r.sides)
+}
 
-            let num_vert_fences = count_fences(&mut vert_fence_segments, true);
-            let num_horz_fences = count_fences(&mut horz_fence_segments, false);
-            score += (num_vert_fences + num_horz_fences) * plots.len();
+/// Generate a `size` by `size` garden where plants tend to clump together into regions
+pub fn generate(seed: u64, size: usize) -> String {
+    const PLANTS: &[u8] = b"ABCDEFGH";
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut grid: Vec<Vec<char>> = Vec::with_capacity(size);
+
+    for y in 0..size {
+        let mut row: Vec<char> = Vec::with_capacity(size);
+        for x in 0..size {
+            // Usually copy a neighbour's plant so regions grow larger than a single plot
+            let plant = match rng.gen_range(0..10) {
+                0..=3 if x > 0 => row[x - 1],
+                4..=7 if y > 0 => grid[y - 1][x],
+                _ => char::from(PLANTS[rng.gen_range(0..PLANTS.len())]),
+            };
+            row.push(plant);
         }
+        grid.push(row);
     }
 
-    score.try_into().unwrap()
+    grid.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
 }
 
 #[cfg(test)]
@@ -199,7 +371,7 @@ BBCD
 BBCC
 EEEC\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 140);
     }
 
@@ -211,10 +383,37 @@ BBCD
 BBCC
 EEEC\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 80);
     }
 
+    #[test]
+    fn test_part2_detailed() {
+        let test_input = "\
+AAAA
+BBCD
+BBCC
+EEEC\n";
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        let result = part2_detailed(&test_path).unwrap();
+        let summary: Vec<(char, u64, u64, u64)> = result
+            .regions
+            .iter()
+            .map(|r| (r.plant, r.area, r.perimeter, r.sides))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ('A', 4, 10, 4),
+                ('B', 4, 8, 4),
+                ('C', 4, 10, 8),
+                ('D', 1, 4, 4),
+                ('E', 3, 8, 4),
+            ]
+        );
+        assert_eq!(result.total, 80);
+    }
+
     #[test]
     fn test_part2_example2() {
         let test_input = "\
@@ -224,7 +423,7 @@ EEEEE
 EXXXX
 EEEEE\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 236);
     }
 
@@ -237,7 +436,7 @@ OOOOO
 OXOXO
 OOOOO\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 436);
     }
 
@@ -251,7 +450,110 @@ ABBAAA
 ABBAAA
 AAAAAA\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 368);
     }
+
+    #[test]
+    fn test_any_plant_labels() {
+        // '.' and digits are plants like any other
+        let test_input = "\
+..0
+.00
+1.1\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let result = part2_detailed(&test_path).unwrap();
+        let summary: Vec<(char, u64, u64, u64)> = result
+            .regions
+            .iter()
+            .map(|r| (r.plant, r.area, r.perimeter, r.sides))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ('.', 3, 8, 6),
+                ('0', 3, 8, 6),
+                ('1', 1, 4, 4),
+                ('.', 1, 4, 4),
+                ('1', 1, 4, 4),
+            ]
+        );
+        assert_eq!(part1(&test_path).unwrap(), 3 * 8 + 3 * 8 + 3 * 4);
+    }
+
+    #[test]
+    fn test_regions() {
+        let test_input = "\
+OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let result = regions(&test_path).unwrap();
+        assert_eq!(result[0].bounding_box, ((0, 0), (4, 4)));
+        assert_eq!(
+            result[0].holes,
+            Some(vec![vec![1], vec![2], vec![3], vec![4]])
+        );
+        assert_eq!(result[1].bounding_box, ((1, 1), (1, 1)));
+        assert_eq!(result[1].holes, Some(vec![]));
+
+        // A hole can hold more than one region, and a region touching the edge isn't a hole
+        let test_input = "\
+AAAAB
+ACDAB
+AAAAB\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let result = regions(&test_path).unwrap();
+        assert_eq!(result[0].holes, Some(vec![vec![2, 3]]));
+        assert_eq!(result[1].holes, Some(vec![]));
+
+        // Pricing the fences doesn't look for holes
+        let result = part1_detailed(&test_path).unwrap();
+        assert!(result.regions.iter().all(|r| r.holes.is_none()));
+    }
+
+    #[test]
+    fn test_render() {
+        let test_input = "\
+AAAA
+BBCD
+BBCC
+EEEC\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let result = render(&test_path).unwrap();
+        assert_eq!(
+            result,
+            "\
+┌───────┐
+│A A A A│
+├───┬─┬─┤
+│B B│C│D│
+│   │ └─┤
+│B B│C C│
+├───┴─┐ │
+│E E E│C│
+└─────┴─┘
+"
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 30);
+        assert_eq!(test_input, generate(2024, 30));
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        let report = part1_detailed(&test_path).unwrap();
+        assert_eq!(report.regions.iter().map(|r| r.area).sum::<u64>(), 900);
+        assert!(report.regions.iter().any(|r| r.area > 1));
+        assert!(part2(&test_path).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(parse_input(""), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("AAB\nAB\n"), Err(Error::Parse(_))));
+    }
 }
@@ -50,14 +50,264 @@ expensive option.
 
 I'm not sure if this is really dynamic programming or not.
 Feels like it since it's a 2D matrix that we're filling in
+
+UPDATE: pushing B first only finds the cheapest option when B is the cheaper button, and
+part 2 needs far more than 100 presses anyway. Both parts now use numeric_solve. The matrix
+is gone too, since it needs a cell for every pair of press counts: when the verify option is
+set, brute_force_solve checks numeric_solve by trying every number of A presses and working
+out the B presses that would finish at the prize, keeping the cheapest.
 */
+use crate::answer::{checked_sum, Answer};
+use crate::error::{Error, Result};
+use crate::options::{self, PuzzleOptions};
+use crate::parse::number;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use regex::Regex;
 use std::path::Path;
 
-const MAX_PRESSES: usize = 100;
+/// The claw machines' limits and prices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Added to both prize coordinates in part 2
+    pub offset: i64,
+    /// The most times each button can be pressed in part 1
+    pub max_presses: usize,
+    pub a_cost: u64,
+    pub b_cost: u64,
+    /// Check the solver against a brute force search on every small enough game
+    pub verify: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            offset: 10_000_000_000_000,
+            max_presses: 100,
+            a_cost: 3,
+            b_cost: 1,
+            verify: false,
+        }
+    }
+}
+
+impl PuzzleOptions for Options {
+    fn values(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("offset", self.offset.to_string()),
+            ("max_presses", self.max_presses.to_string()),
+            ("a_cost", self.a_cost.to_string()),
+            ("b_cost", self.b_cost.to_string()),
+            ("verify", self.verify.to_string()),
+        ]
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "offset" => self.offset = options::value(key, value)?,
+            "max_presses" => self.max_presses = options::value(key, value)?,
+            "a_cost" => self.a_cost = options::value(key, value)?,
+            "b_cost" => self.b_cost = options::value(key, value)?,
+            "verify" => self.verify = options::value(key, value)?,
+            _ => return Err(options::unknown(key)),
+        }
+        Ok(())
+    }
+}
+
+/// A single claw machine and how (if at all) its prize was won
+#[pyclass(get_all, frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+    pub a_button: (i64, i64),
+    pub b_button: (i64, i64),
+    pub prize: (i64, i64),
+    pub presses: Option<(u64, u64)>,
+    pub tokens: Option<u64>,
+}
+
+/// A claw machine where the brute force search disagreed with the solver
+#[pyclass(get_all, frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub game: usize,
+    pub presses: Option<(u64, u64)>,
+    pub oracle_presses: Option<(u64, u64)>,
+}
+
+/// Every claw machine played and the total tokens spent winning prizes
+#[pyclass(get_all, frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClawReport {
+    pub games: Vec<GameResult>,
+    pub total: u64,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl ClawReport {
+    fn new(
+        games: &[Game],
+        presses: Vec<Option<(u64, u64)>>,
+        mismatches: Vec<Mismatch>,
+        options: &Options,
+    ) -> Result<Self> {
+        let games = games
+            .iter()
+            .zip(presses)
+            .map(|(g, presses)| {
+                Ok(GameResult {
+                    a_button: g.a_button,
+                    b_button: g.b_button,
+                    prize: g.prize,
+                    presses,
+                    tokens: tokens(presses, options)?,
+                })
+            })
+            .collect::<Result<Vec<GameResult>>>()?;
+        let total = checked_sum(games.iter().filter_map(|g| g.tokens))?;
+        Ok(Self {
+            games,
+            total,
+            mismatches,
+        })
+    }
+
+    /// The total, unless verifying found games the solvers disagree on
+    fn verified_total(self) -> Result<Answer> {
+        if self.mismatches.is_empty() {
+            Ok(self.total.into())
+        } else {
+            let games: Vec<usize> = self.mismatches.iter().map(|m| m.game).collect();
+            Err(Error::Mismatch(format!(
+                "the brute force search won claw machines {games:?} differently"
+            )))
+        }
+    }
+}
+
+fn tokens(presses: Option<(u64, u64)>, options: &Options) -> Result<Option<u64>> {
+    presses
+        .map(|(a, b)| {
+            let b_tokens = b.checked_mul(options.b_cost)?;
+            a.checked_mul(options.a_cost)?.checked_add(b_tokens)
+        })
+        .map(|t| t.ok_or(Error::Overflow))
+        .transpose()
+}
+
+fn cross(u: (i64, i64), v: (i64, i64)) -> Result<i64> {
+    // u.x * v.y - v.x * u.y, without silently wrapping on large inputs
+    u.0.checked_mul(v.1)
+        .zip(v.0.checked_mul(u.1))
+        .and_then(|(l, r)| l.checked_sub(r))
+        .ok_or(Error::Overflow)
+}
+
+/// Returns (gcd, x, y) where a * x + b * y = gcd(a, b)
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (gcd, x, y) = extended_gcd(b, a % b);
+        (gcd, y, x - (a / b) * y)
+    }
+}
+
+const fn floor_div(n: i128, d: i128) -> i128 {
+    let q = n / d;
+    if n % d != 0 && (n < 0) != (d < 0) {
+        q - 1
+    } else {
+        q
+    }
+}
+
+const fn ceil_div(n: i128, d: i128) -> i128 {
+    -floor_div(-n, d)
+}
+
+/// The cheapest presses moving the claw `a_move` and `b_move` along a line to reach `prize`
+fn cheapest_presses(
+    a_move: i128,
+    b_move: i128,
+    prize: i128,
+    costs: (i128, i128),
+    max_presses: Option<i128>,
+) -> Result<Option<(u64, u64)>> {
+    let presses = |a_presses: i128, b_presses: i128| -> Result<Option<(u64, u64)>> {
+        let a_presses = u64::try_from(a_presses).map_err(|_| Error::Overflow)?;
+        let b_presses = u64::try_from(b_presses).map_err(|_| Error::Overflow)?;
+        Ok(Some((a_presses, b_presses)))
+    };
+    let allowed = |n: i128| n >= 0 && max_presses.is_none_or(|m| n <= m);
+
+    // A button that doesn't move the claw is never worth pressing
+    match (a_move, b_move) {
+        (0, 0) if prize == 0 => return presses(0, 0),
+        (0, 0) => return Ok(None),
+        (0, b) if prize % b == 0 && allowed(prize / b) => return presses(0, prize / b),
+        (a, 0) if prize % a == 0 && allowed(prize / a) => return presses(prize / a, 0),
+        (0, _) | (_, 0) => return Ok(None),
+        _ => {}
+    }
+
+    let (gcd, x, y) = extended_gcd(a_move, b_move);
+    if prize % gcd != 0 {
+        return Ok(None);
+    }
+
+    // Every solution is (a0 + a_step * t, b0 - b_step * t) for some integer t
+    let (a0, b0) = (x * (prize / gcd), y * (prize / gcd));
+    let (a_step, b_step) = (b_move / gcd, a_move / gcd);
+
+    // Each button is pressed between 0 and max_presses times, which bounds t
+    let (mut lowest, mut highest) = (None, None);
+    let mut press_range = |start: i128, step: i128| {
+        let (min_t, max_t) = if step > 0 {
+            let max_t = max_presses.map(|m| floor_div(m - start, step));
+            (Some(ceil_div(-start, step)), max_t)
+        } else {
+            let min_t = max_presses.map(|m| ceil_div(m - start, step));
+            (min_t, Some(floor_div(-start, step)))
+        };
+        if let Some(t) = min_t {
+            lowest = Some(lowest.map_or(t, |l: i128| l.max(t)));
+        }
+        if let Some(t) = max_t {
+            highest = Some(highest.map_or(t, |h: i128| h.min(t)));
+        }
+    };
+    press_range(a0, a_step);
+    press_range(b0, -b_step);
+
+    if let (Some(l), Some(h)) = (lowest, highest) {
+        if l > h {
+            return Ok(None);
+        }
+    }
+
+    // The cost changes by the same amount for every step of t, so the cheapest
+    // solution is at whichever end of the range the cost goes down towards
+    let cost_per_step = costs
+        .0
+        .checked_mul(a_step)
+        .zip(costs.1.checked_mul(b_step))
+        .and_then(|(a_cost, b_cost)| a_cost.checked_sub(b_cost))
+        .ok_or(Error::Overflow)?;
+    let Some(t) = (if cost_per_step > 0 {
+        lowest.or(highest)
+    } else {
+        highest.or(lowest)
+    }) else {
+        return Ok(None);
+    };
+
+    presses(a0 + a_step * t, b0 - b_step * t)
+}
 
 #[derive(Debug)]
-struct Game {
+pub struct Game {
     a_button: (i64, i64),
     b_button: (i64, i64),
     prize: (i64, i64),
@@ -72,9 +322,14 @@ impl Game {
         }
     }
 
-    fn numeric_solve(&mut self) -> Option<(usize, usize)> {
-        let b_press_numer = self.a_button.0 * self.prize.1 - self.prize.0 * self.a_button.1;
-        let b_press_denom = self.a_button.0 * self.b_button.1 - self.b_button.0 * self.a_button.1;
+    /// The cheapest way to win the prize, pressing each button at most `max_presses` times
+    fn numeric_solve(
+        &self,
+        options: &Options,
+        max_presses: Option<usize>,
+    ) -> Result<Option<(u64, u64)>> {
+        let b_press_numer = cross(self.a_button, self.prize)?;
+        let b_press_denom = cross(self.a_button, self.b_button)?;
 
         // Special case to handle where a_button and b_button are "on the same line"
         // which would cause the b_press_denom to be 0 and cause an issue, for example
@@ -82,109 +337,291 @@ impl Game {
         //
         // ok, they didn't include any of these cases :)
         if b_press_denom == 0 {
-            println!("THEY WERE MEAN!! {:?},{:?}", self.a_button, self.b_button);
+            return self.collinear_solve(options, max_presses);
         }
 
-        if b_press_numer % b_press_denom != 0 {
-            None
+        if b_press_numer
+            .checked_rem(b_press_denom)
+            .ok_or(Error::Overflow)?
+            != 0
+        {
+            Ok(None)
         } else {
-            let b_presses = b_press_numer / b_press_denom;
-
-            let a_press_numer = self.prize.0 - b_presses * self.b_button.0;
-            let a_press_denom = self.a_button.0;
+            let b_presses = b_press_numer
+                .checked_div(b_press_denom)
+                .ok_or(Error::Overflow)?;
 
-            if a_press_numer % a_press_denom != 0 {
-                None
+            // Solve for A along whichever axis it actually moves the claw
+            let (a_step, b_step, prize) = if self.a_button.0 == 0 {
+                (self.a_button.1, self.b_button.1, self.prize.1)
+            } else {
+                (self.a_button.0, self.b_button.0, self.prize.0)
+            };
+            let a_press_numer = b_presses
+                .checked_mul(b_step)
+                .and_then(|b_x| prize.checked_sub(b_x))
+                .ok_or(Error::Overflow)?;
+            let a_press_denom = a_step;
+
+            if a_press_numer
+                .checked_rem(a_press_denom)
+                .ok_or(Error::Overflow)?
+                != 0
+            {
+                Ok(None)
             } else {
-                let a_presses = a_press_numer / a_press_denom;
-                Some((a_presses as usize, b_presses as usize))
+                let a_presses = a_press_numer
+                    .checked_div(a_press_denom)
+                    .ok_or(Error::Overflow)?;
+
+                // A negative number of presses isn't a way to win the prize
+                let presses = u64::try_from(a_presses)
+                    .ok()
+                    .zip(u64::try_from(b_presses).ok());
+                let limit = max_presses.map(|m| u64::try_from(m).unwrap_or(u64::MAX));
+                Ok(presses.filter(|&(a, b)| limit.is_none_or(|m| a <= m && b <= m)))
             }
         }
     }
 
-    fn dp_solve(&mut self) -> Option<(usize, usize)> {
-        let mut presses = vec![(0, 0)];
-        let mut moves = [[(0, 0); MAX_PRESSES + 1]; MAX_PRESSES + 1];
-        let mut visited = [[false; MAX_PRESSES + 1]; MAX_PRESSES + 1];
+    fn collinear_solve(
+        &self,
+        options: &Options,
+        max_presses: Option<usize>,
+    ) -> Result<Option<(u64, u64)>> {
+        // The prize has to be on the line too, otherwise no presses can reach it
+        if cross(self.a_button, self.prize)? != 0 || cross(self.b_button, self.prize)? != 0 {
+            return Ok(None);
+        }
 
-        while let Some((a, b)) = presses.pop() {
-            // Found the prize, return the num of A and B presses
-            if moves[a][b] == self.prize {
-                return Some((a, b));
-            }
+        // Everything is on one line, so reaching the prize along one (moving) axis is enough
+        let (a_step, b_step, prize) = if self.a_button.0 == 0 && self.b_button.0 == 0 {
+            (self.a_button.1, self.b_button.1, self.prize.1)
+        } else {
+            (self.a_button.0, self.b_button.0, self.prize.0)
+        };
+
+        cheapest_presses(
+            i128::from(a_step),
+            i128::from(b_step),
+            i128::from(prize),
+            (i128::from(options.a_cost), i128::from(options.b_cost)),
+            max_presses.map(|m| i128::try_from(m).unwrap_or(i128::MAX)),
+        )
+    }
 
-            visited[a][b] = true;
+    /// Whether every way to win the prize presses each button at most `max_presses` times,
+    /// so that the brute force search can't miss any of them
+    fn is_small(&self, max_presses: usize) -> bool {
+        let axes = [
+            (self.a_button.0, self.b_button.0, self.prize.0),
+            (self.a_button.1, self.b_button.1, self.prize.1),
+        ];
+        let max_presses = i128::try_from(max_presses).unwrap_or(i128::MAX);
+
+        axes.iter().all(|&(a, b, p)| a >= 0 && b >= 0 && p >= 0)
+            && axes.iter().any(|&(a, b, p)| {
+                a > 0 && b > 0 && i128::from(p) <= i128::from(a.min(b)).saturating_mul(max_presses)
+            })
+    }
 
-            let (x, y) = moves[a][b];
+    /// The number of B presses that moves the claw by `rest`, if there is one
+    fn b_presses(&self, rest: (i128, i128)) -> Option<i128> {
+        let (bx, by) = (i128::from(self.b_button.0), i128::from(self.b_button.1));
+        let b = match (bx, by) {
+            (0, 0) => 0,
+            (0, _) if rest.1 % by != 0 => return None,
+            (0, _) => rest.1 / by,
+            _ if rest.0 % bx != 0 => return None,
+            _ => rest.0 / bx,
+        };
+        (b.checked_mul(bx) == Some(rest.0) && b.checked_mul(by) == Some(rest.1)).then_some(b)
+    }
 
-            // Calculate the position if using one more B press
-            if a < MAX_PRESSES {
-                let new_x = x + self.a_button.0;
-                let new_y = y + self.a_button.1;
-                let not_too_big = new_x <= self.prize.0 && new_y <= self.prize.1;
+    /// Try every number of A presses up to `max_presses` with whichever number of B presses
+    /// finishes at the prize, only used to check `numeric_solve`
+    fn brute_force_solve(
+        &self,
+        max_presses: usize,
+        options: &Options,
+    ) -> Result<Option<(u64, u64)>> {
+        let max_presses = i128::try_from(max_presses).map_err(|_| Error::Overflow)?;
+        let mut cheapest: Option<(u64, (u64, u64))> = None;
+
+        for a in 0..=max_presses {
+            let (Some(ax), Some(ay)) = (
+                a.checked_mul(i128::from(self.a_button.0)),
+                a.checked_mul(i128::from(self.a_button.1)),
+            ) else {
+                break;
+            };
+            let rest = (i128::from(self.prize.0) - ax, i128::from(self.prize.1) - ay);
+            let Some(b) = self
+                .b_presses(rest)
+                .filter(|b| (0..=max_presses).contains(b))
+            else {
+                continue;
+            };
+
+            // Found the prize, keep the num of A and B presses if they're the cheapest so far
+            let win = Some((
+                u64::try_from(a).map_err(|_| Error::Overflow)?,
+                u64::try_from(b).map_err(|_| Error::Overflow)?,
+            ));
+            let cost = tokens(win, options)?.unwrap_or(u64::MAX);
+            if cheapest.is_none_or(|(c, _)| cost < c) {
+                cheapest = win.map(|w| (cost, w));
+            }
+        }
+        Ok(cheapest.map(|(_, win)| win))
+    }
+}
 
-                if not_too_big && !visited[a + 1][b] {
-                    moves[a + 1][b] = (new_x, new_y);
-                    presses.push((a + 1, b));
-                }
+/// Win as many prizes as possible, checking against the brute force search if asked
+fn play(games: &[Game], options: &Options, max_presses: Option<usize>) -> Result<ClawReport> {
+    let button_presses = games
+        .iter()
+        .map(|g| g.numeric_solve(options, max_presses))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut mismatches = vec![];
+    if options.verify {
+        for (i, (g, &presses)) in games.iter().zip(&button_presses).enumerate() {
+            if !g.is_small(options.max_presses) {
+                continue;
             }
 
-            // Calculate the position if using one more B press
-            // NOTE, doing this second so it's at the top of the stack
-            //       because b presses are cheaper
-            if b < MAX_PRESSES {
-                let new_x = x + self.b_button.0;
-                let new_y = y + self.b_button.1;
-                let not_too_big = new_x <= self.prize.0 && new_y <= self.prize.1;
-
-                if not_too_big && !visited[a][b + 1] {
-                    moves[a][b + 1] = (new_x, new_y);
-                    presses.push((a, b + 1));
-                }
+            // Ties in cost can be won with different presses, so compare the tokens spent
+            let oracle_presses = g.brute_force_solve(options.max_presses, options)?;
+            if tokens(presses, options)? != tokens(oracle_presses, options)? {
+                mismatches.push(Mismatch {
+                    game: i,
+                    presses,
+                    oracle_presses,
+                });
             }
         }
-        None
     }
+
+    ClawReport::new(games, button_presses, mismatches, options)
 }
 
-fn parse_xy(s: &str) -> (i64, i64) {
+fn parse_xy(s: &str, line: usize) -> Result<(i64, i64)> {
     let xy_re = Regex::new(r"X.(\d+).*Y.(\d+)").unwrap();
-    let m = &xy_re.captures_iter(s).next().unwrap();
-    let x = m[1].parse().unwrap();
-    let y = m[2].parse().unwrap();
-    (x, y)
+    let Some(m) = xy_re.captures(s) else {
+        return Err(Error::Parse(format!(
+            "line {}: expected X and Y values",
+            line + 1
+        )));
+    };
+    Ok((number(&m[1], line)?, number(&m[2], line)?))
 }
 
-pub fn part1(data_path: &Path) -> u64 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+/// Read each claw machine's A button, B button and prize location
+pub fn parse_input(text: &str) -> Result<Vec<Game>> {
     let lines: Vec<&str> = text.lines().collect();
-    let mut games: Vec<_> = lines
+    lines
         .chunks(4)
-        .map(|s| Game::new(parse_xy(s[0]), parse_xy(s[1]), parse_xy(s[2])))
-        .collect();
+        .enumerate()
+        .map(|(i, s)| {
+            let [a, b, prize, ..] = s[..] else {
+                return Err(Error::Parse(format!(
+                    "line {}: expected a claw machine's buttons and prize",
+                    4 * i + 1
+                )));
+            };
+            Ok(Game::new(
+                parse_xy(a, 4 * i)?,
+                parse_xy(b, 4 * i + 1)?,
+                parse_xy(prize, 4 * i + 2)?,
+            ))
+        })
+        .collect()
+}
 
-    let button_presses: Vec<_> = games.iter_mut().filter_map(|g| g.dp_solve()).collect();
-    let cost: usize = button_presses.iter().map(|(a, b)| a * 3 + b).sum();
-    cost.try_into().unwrap()
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    part1_with(data_path, &Options::default())
 }
 
-pub fn part2(data_path: &Path) -> u64 {
-    let text = std::fs::read_to_string(data_path).unwrap();
-    let lines: Vec<&str> = text.lines().collect();
-    let mut games: Vec<_> = lines
-        .chunks(4)
-        .map(|s| Game::new(parse_xy(s[0]), parse_xy(s[1]), parse_xy(s[2])))
-        .collect();
+pub fn part1_with(data_path: &Path, options: &Options) -> Result<Answer> {
+    part1_detailed(data_path, options)?.verified_total()
+}
+
+pub fn part1_detailed(data_path: &Path, options: &Options) -> Result<ClawReport> {
+    let text = std::fs::read_to_string(data_path)?;
+    let games = parse_input(&text)?;
+
+    play(&games, options, Some(options.max_presses))
+}
+
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    part2_with(data_path, &Options::default())
+}
+
+pub fn part2_with(data_path: &Path, options: &Options) -> Result<Answer> {
+    part2_detailed(data_path, options)?.verified_total()
+}
+
+pub fn part2_detailed(data_path: &Path, options: &Options) -> Result<ClawReport> {
+    let text = std::fs::read_to_string(data_path)?;
+    let mut games = parse_input(&text)?;
 
     // Add the 10000000000000 offsets
-    let offset = 10000000000000;
+    let offset = options.offset;
     for g in &mut games {
-        g.prize = (g.prize.0 + offset, g.prize.1 + offset);
+        g.prize = g
+            .prize
+            .0
+            .checked_add(offset)
+            .zip(g.prize.1.checked_add(offset))
+            .ok_or(Error::Overflow)?;
     }
 
-    let button_presses: Vec<_> = games.iter_mut().filter_map(|g| g.numeric_solve()).collect();
-    let cost: usize = button_presses.iter().map(|(a, b)| a * 3 + b).sum();
-    cost.try_into().unwrap()
+    play(&games, options, None)
+}
+
+/// Generate `size` claw machines that can all (`Some(true)`) or never (`Some(false)`) be won
+/// within 100 presses of each button, or a random mix of the two (`None`)
+pub fn generate(seed: u64, size: usize, solvable: Option<bool>) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut games = Vec::with_capacity(size);
+
+    while games.len() < size {
+        let a_button = (rng.gen_range(10..100), rng.gen_range(10..100));
+        let b_button = (rng.gen_range(10..100), rng.gen_range(10..100));
+
+        // Collinear buttons have many (or no) ways to reach a prize, keep to the puzzle's cases
+        if cross(a_button, b_button).unwrap() == 0 {
+            continue;
+        }
+
+        let winnable = solvable.unwrap_or_else(|| rng.gen_bool(0.5));
+        let prize = if winnable {
+            let (a, b): (i64, i64) = (rng.gen_range(0..=100), rng.gen_range(0..=100));
+            (
+                a * a_button.0 + b * b_button.0,
+                a * a_button.1 + b * b_button.1,
+            )
+        } else {
+            let prize = (rng.gen_range(0..20_000), rng.gen_range(0..20_000));
+            let game = Game::new(a_button, b_button, prize);
+            if game
+                .numeric_solve(&Options::default(), None)
+                .unwrap()
+                .is_some()
+            {
+                continue;
+            }
+            prize
+        };
+
+        games.push(format!(
+            "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}\n",
+            a_button.0, a_button.1, b_button.0, b_button.1, prize.0, prize.1
+        ));
+    }
+
+    games.join("\n")
 }
 
 #[cfg(test)]
@@ -225,10 +662,37 @@ Button A: X+69, Y+23
 Button B: X+27, Y+71
 Prize: X=18641, Y=10279\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 480);
     }
 
+    #[test]
+    fn test_part1_detailed() {
+        let test_input = "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279\n";
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        let result = part1_detailed(&test_path, &Options::default()).unwrap();
+        let presses: Vec<_> = result.games.iter().map(|g| g.presses).collect();
+        assert_eq!(presses, vec![Some((80, 40)), None, Some((38, 86)), None]);
+        let tokens: Vec<_> = result.games.iter().map(|g| g.tokens).collect();
+        assert_eq!(tokens, vec![Some(280), None, Some(200), None]);
+        assert_eq!(result.total, 480);
+    }
+
     #[test]
     fn test_part2() {
         let test_input = "\
@@ -248,7 +712,183 @@ Button A: X+69, Y+23
 Button B: X+27, Y+71
 Prize: X=18641, Y=10279\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 875318608908);
     }
+
+    #[test]
+    fn test_options() {
+        let test_input = "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+
+        let mut options = Options::default();
+        options.set("a_cost", "1").unwrap();
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 120 + 124);
+
+        // Without the offset part 2 wins the same prizes as part 1
+        options.set("offset", "0").unwrap();
+        assert_eq!(part2_with(&test_path, &options).unwrap(), 120 + 124);
+
+        // Both winnable machines need the A button pressed more than 30 times
+        options.set("max_presses", "30").unwrap();
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_collinear_buttons() {
+        let options = Options::default();
+        let solve =
+            |a, b, prize, options| Game::new(a, b, prize).numeric_solve(options, None).unwrap();
+
+        // (5, 0) and (1, 1) both reach the prize but B presses are cheaper
+        assert_eq!(solve((1, 2), (4, 8), (5, 10), &options), Some((1, 1)));
+        let expensive_b = Options {
+            a_cost: 1,
+            b_cost: 10,
+            ..Options::default()
+        };
+        assert_eq!(solve((1, 2), (4, 8), (5, 10), &expensive_b), Some((5, 0)));
+
+        assert_eq!(solve((0, 1), (0, 3), (0, 7), &options), Some((1, 2)));
+        assert_eq!(solve((2, 4), (4, 8), (3, 6), &options), None);
+        assert_eq!(solve((1, 2), (2, 4), (3, 5), &options), None);
+        assert_eq!(solve((0, 0), (0, 0), (0, 0), &options), Some((0, 0)));
+        assert_eq!(solve((0, 0), (3, 3), (6, 6), &options), Some((0, 2)));
+        assert_eq!(solve((2, 2), (-3, -3), (1, 1), &options), Some((2, 1)));
+
+        // Part 1's limit on presses can leave only the more expensive way to win
+        let game = Game::new((1, 1), (2, 2), (300, 300));
+        assert_eq!(game.numeric_solve(&options, None).unwrap(), Some((0, 150)));
+        assert_eq!(
+            game.numeric_solve(&options, Some(100)).unwrap(),
+            Some((100, 100))
+        );
+        assert_eq!(game.numeric_solve(&options, Some(99)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_verify() {
+        let options = Options {
+            verify: true,
+            ..Options::default()
+        };
+        let test_input = "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 280);
+        assert!(part2_with(&test_path, &options).is_ok());
+
+        // The brute force search takes time but no memory for each press it can make
+        let many_presses = Options {
+            max_presses: 1_000_000,
+            ..options
+        };
+        assert_eq!(part1_with(&test_path, &many_presses).unwrap(), 280);
+
+        let collinear = Options {
+            offset: 0,
+            verify: true,
+            ..Options::default()
+        };
+        let test_input = "\
+Button A: X+1, Y+2
+Button B: X+4, Y+8
+Prize: X=5, Y=10
+
+Button A: X+3, Y+3
+Button B: X+1, Y+1
+Prize: X=200, Y=200
+";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let report = part1_detailed(&test_path, &options).unwrap();
+        assert!(report.mismatches.is_empty());
+        // Every way to win the second prize costs 200 tokens, so the presses may differ
+        assert_eq!(report.total, 204);
+        let report = part2_detailed(&test_path, &collinear).unwrap();
+        assert!(report.mismatches.is_empty());
+
+        for seed in 0..5 {
+            let (_d, _f, test_path) = create_test_file(&generate(seed, 50, None));
+            assert!(part1_with(&test_path, &options).is_ok());
+            assert!(part2_with(&test_path, &collinear).is_ok());
+        }
+
+        // The brute force search can't see past max_presses, so it disagrees on big prizes
+        let game = Game::new((1, 1), (2, 2), (300, 300));
+        assert!(!game.is_small(100));
+        assert_eq!(
+            game.brute_force_solve(100, &options).unwrap(),
+            Some((100, 100))
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 50, None);
+        assert_eq!(test_input, generate(2024, 50, None));
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        let report = part1_detailed(&test_path, &Options::default()).unwrap();
+        assert_eq!(report.games.len(), 50);
+        assert!(report.games.iter().any(|g| g.presses.is_some()));
+        assert!(report.games.iter().any(|g| g.presses.is_none()));
+        assert!(part2(&test_path).is_ok());
+
+        let test_input = generate(2024, 50, Some(true));
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        let report = part1_detailed(&test_path, &Options::default()).unwrap();
+        assert!(report.games.iter().all(|g| g.presses.is_some()));
+
+        let test_input = generate(2024, 50, Some(false));
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        let report = part1_detailed(&test_path, &Options::default()).unwrap();
+        assert!(report.games.iter().all(|g| g.presses.is_none()));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            parse_input("Button A: X+94, Y+34\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            parse_input("Button A: X+94, Y+34\nButton B: X+22\nPrize: X=8400, Y=5400\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            parse_input("Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=99999999999999999999\n"),
+            Err(Error::Parse(_))
+        ));
+
+        let game = Game::new((0, 2), (4, 8), (8, 20));
+        assert_eq!(
+            game.numeric_solve(&Options::default(), None).unwrap(),
+            Some((2, 2))
+        );
+
+        // A negative number of presses can't win the prize
+        let game = Game::new((1, 0), (0, 1), (-1, 2));
+        assert_eq!(game.numeric_solve(&Options::default(), None).unwrap(), None);
+    }
 }
@@ -1,3 +1,8 @@
+use crate::answer::{to_u64, Answer};
+use crate::error::{Error, Result};
+use crate::parse::char_grid;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::path::Path;
 
 #[allow(dead_code)]
@@ -20,7 +25,7 @@ fn get_robot_loc(grid: &Vec<Vec<char>>) -> Option<(i32, i32)> {
     None
 }
 
-fn score_warehouse(grid: &Vec<Vec<char>>) -> u32 {
+fn score_warehouse(grid: &Vec<Vec<char>>) -> Result<u64> {
     let mut score = 0;
     for y in 0..grid.len() {
         for x in 0..grid[y].len() {
@@ -29,7 +34,7 @@ fn score_warehouse(grid: &Vec<Vec<char>>) -> u32 {
             }
         }
     }
-    score.try_into().unwrap()
+    to_u64(score)
 }
 fn make_move(grid: &mut Vec<Vec<char>>, pos: (i32, i32), m: char) -> (i32, i32) {
     // Determine the direction of the attempted move
@@ -78,22 +83,46 @@ fn make_move(grid: &mut Vec<Vec<char>>, pos: (i32, i32), m: char) -> (i32, i32)
     }
 }
 
-pub fn part1(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
-
-    // Read in the warehouse grid
-    let mut grid: Vec<Vec<char>> = text
-        .lines()
-        .take_while(|l| l.len() > 0)
-        .map(|l| l.chars().collect())
-        .collect();
+/// Read the walled warehouse grid and the robot's moves
+pub fn parse_input(text: &str) -> Result<(Vec<Vec<char>>, String)> {
+    let warehouse: Vec<&str> = text.lines().take_while(|l| l.len() > 0).collect();
+    let warehouse = warehouse.join("\n");
+    let grid = char_grid(&warehouse)?;
+
+    // Moves are never checked against the edge of the grid, so it has to be walled in
+    let (height, width) = (grid.len(), grid[0].len());
+    for (y, row) in grid.iter().enumerate() {
+        for (x, &c) in row.iter().enumerate() {
+            let on_edge = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+            if !"#.O@".contains(c) || (on_edge && c != '#') {
+                return Err(Error::Parse(format!(
+                    "line {}: unexpected square {c:?} at x={x}",
+                    y + 1
+                )));
+            }
+        }
+    }
+    if warehouse.matches('@').count() != 1 {
+        return Err(Error::Parse("expected exactly one robot".to_string()));
+    }
 
-    // Read in the moves
     let moves = text
         .lines()
         .skip_while(|l| l.len() > 0)
         .collect::<Vec<&str>>()
         .join("");
+    if let Some(m) = moves.chars().find(|&m| !"<>^v".contains(m)) {
+        return Err(Error::Parse(format!("unexpected move {m:?}")));
+    }
+
+    Ok((grid, moves))
+}
+
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
+
+    // Read in the warehouse grid and the moves
+    let (mut grid, moves) = parse_input(&text)?;
 
     // Find where the robot starts
     let mut pos = get_robot_loc(&grid).unwrap();
@@ -105,7 +134,49 @@ pub fn part1(data_path: &Path) -> u32 {
     }
 
     // Score the warehouse
-    score_warehouse(&grid)
+    Ok(score_warehouse(&grid)?.into())
+}
+
+/// Generate a `size` by `size` walled warehouse full of boxes, and `10 * size` robot moves
+pub fn generate(seed: u64, size: usize) -> String {
+    const MOVES: [char; 4] = ['^', '>', 'v', '<'];
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // The warehouse needs walls all the way around and at least one square inside for the robot
+    let size = size.max(3);
+    let mut grid: Vec<Vec<char>> = (0..size)
+        .map(|y| {
+            (0..size)
+                .map(|x| {
+                    if x == 0 || y == 0 || x == size - 1 || y == size - 1 {
+                        '#'
+                    } else {
+                        match rng.gen_range(0..100) {
+                            0..=4 => '#',
+                            5..=24 => 'O',
+                            _ => '.',
+                        }
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    grid[rng.gen_range(1..size - 1)][rng.gen_range(1..size - 1)] = '@';
+
+    let mut text: String = grid
+        .iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect();
+    text.push('\n');
+
+    let moves: Vec<char> = (0..10 * size).map(|_| MOVES[rng.gen_range(0..4)]).collect();
+    for line in moves.chunks(70) {
+        text.push_str(&line.iter().collect::<String>());
+        text.push('\n');
+    }
+
+    text
 }
 
 #[cfg(test)]
@@ -141,7 +212,39 @@ mod tests {
 
 <^^>>>vv<v>>v<<\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 2028);
     }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 20);
+        assert_eq!(test_input, generate(2024, 20));
+        assert_eq!(test_input.matches('@').count(), 1);
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert!(part1(&test_path).unwrap() != 0);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            parse_input("###\n#@#\n###\n\n<>x\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            parse_input("###\n#@.\n###\n\n<>\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            parse_input("###\n#.#\n###\n\n<>\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            parse_input("####\n#@@#\n####\n\n<>\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(parse_input("\n<>\n"), Err(Error::Parse(_))));
+        assert_eq!(parse_input("###\n#@#\n###\n").unwrap().1, "");
+    }
 }
@@ -1,80 +1,219 @@
-use std::cmp::{max, min};
+use crate::answer::{to_u64, Answer};
+use crate::error::{Error, Result};
+use crate::options::{self, PuzzleOptions};
+use crate::parse::number;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::path::Path;
 
-fn validate_report(r: &[i32]) -> bool {
-    let pairs = r.iter().zip(r.iter().skip(1));
-    let diffs: Vec<i32> = pairs.map(|(x, y)| x - y).collect();
+/// How far apart adjacent levels of a safe report can be, and how many levels the Problem
+/// Dampener can remove
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub min_step: i64,
+    pub max_step: i64,
+    pub max_removals: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            min_step: 1,
+            max_step: 3,
+            max_removals: 1,
+        }
+    }
+}
+
+impl PuzzleOptions for Options {
+    fn values(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("min_step", self.min_step.to_string()),
+            ("max_step", self.max_step.to_string()),
+            ("max_removals", self.max_removals.to_string()),
+        ]
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "min_step" => self.min_step = options::value(key, value)?,
+            "max_step" => self.max_step = options::value(key, value)?,
+            "max_removals" => self.max_removals = options::value(key, value)?,
+            _ => return Err(options::unknown(key)),
+        }
+        Ok(())
+    }
+}
+
+/// Whether stepping from level `a` to `b` keeps a report safe while it goes in `direction`
+fn is_safe_step(a: i32, b: i32, direction: i64, options: &Options) -> bool {
+    let step = direction * (i64::from(b) - i64::from(a));
+    (options.min_step.max(0)..=options.max_step).contains(&step)
+}
+
+/// How a level can be kept in a safe report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kept {
+    Unreachable,
+    First,
+    After(usize),
+}
+
+/// The fewest levels to remove to make a report safe while it goes in `direction`
+fn dampen_direction(
+    report: &[i32],
+    options: &Options,
+    max_removals: usize,
+    direction: i64,
+) -> Option<Vec<usize>> {
+    let n = report.len();
+    let k = max_removals.min(n);
+
+    // How each level can be kept with each number of levels before it removed, where only the
+    // k levels before it can be the level kept straight before it
+    let mut kept = vec![vec![Kept::Unreachable; k + 1]; n];
+    for i in 0..n {
+        if i <= k {
+            kept[i][i] = Kept::First;
+        }
+        for skipped in 0..i.min(k + 1) {
+            let prev = i - 1 - skipped;
+            if !is_safe_step(report[prev], report[i], direction, options) {
+                continue;
+            }
+            for removed in 0..=k - skipped {
+                if kept[prev][removed] != Kept::Unreachable
+                    && kept[i][removed + skipped] == Kept::Unreachable
+                {
+                    kept[i][removed + skipped] = Kept::After(prev);
+                }
+            }
+        }
+    }
 
-    let min_diff = *diffs.iter().min().unwrap();
-    let max_diff = *diffs.iter().max().unwrap();
+    // Every level after the last one kept is removed too
+    let (mut i, mut removed) = (n.saturating_sub(k + 1)..n)
+        .flat_map(|i| (0..=k).map(move |removed| (i, removed)))
+        .filter(|&(i, removed)| removed + n - 1 - i <= k && kept[i][removed] != Kept::Unreachable)
+        .min_by_key(|&(i, removed)| removed + n - 1 - i)?;
 
-    // False if they have different signs
-    if min_diff * max_diff < 0 {
-        return false;
-    };
+    let mut is_kept = vec![false; n];
+    loop {
+        is_kept[i] = true;
+        let Kept::After(prev) = kept[i][removed] else {
+            break;
+        };
+        removed -= i - prev - 1;
+        i = prev;
+    }
+    Some((0..n).filter(|&i| !is_kept[i]).collect())
+}
 
-    let (abs_min, abs_max) = (
-        min(min_diff.abs(), max_diff.abs()),
-        max(min_diff.abs(), max_diff.abs()),
-    );
+/// The indices of the fewest levels, at most `max_removals`, to remove to make a report safe
+///
+/// Each level only looks back `max_removals + 1` levels for the level kept before it, so this
+/// takes time linear in the length of the report.
+pub fn dampen(report: &[i32], options: &Options, max_removals: usize) -> Option<Vec<usize>> {
+    [1, -1]
+        .into_iter()
+        .filter_map(|direction| dampen_direction(report, options, max_removals, direction))
+        .min_by_key(Vec::len)
+}
 
-    // False if the min abs diff is too small
-    if abs_min < 1 {
-        return false;
-    };
+fn validate_report(r: &[i32], options: &Options) -> bool {
+    dampen(r, options, 0).is_some()
+}
 
-    // False if the max abs diff is too big
-    if abs_max > 3 {
-        return false;
-    };
+/// Read one report of levels per line
+pub fn parse_input(text: &str) -> Result<Vec<Vec<i32>>> {
+    text.lines()
+        .enumerate()
+        .map(|(i, l)| {
+            let report = l
+                .split_whitespace()
+                .map(|n| number(n, i))
+                .collect::<Result<Vec<i32>>>()?;
+            if report.is_empty() {
+                return Err(Error::Parse(format!("line {}: expected a report", i + 1)));
+            }
+            Ok(report)
+        })
+        .collect()
+}
 
-    true
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    part1_with(data_path, &Options::default())
 }
 
-pub fn part1(data_path: &Path) -> u32 {
+pub fn part1_with(data_path: &Path, options: &Options) -> Result<Answer> {
     // Read in the entire file
-    let text = std::fs::read_to_string(data_path).unwrap();
+    let text = std::fs::read_to_string(data_path)?;
 
-    let reports: Vec<Vec<i32>> = text
-        .lines()
-        .map(|l| l.split_whitespace().map(|n| n.parse().unwrap()).collect())
-        .collect();
+    let reports = parse_input(&text)?;
 
     let num_safe_reports = reports
         .iter()
-        .map(|r| if validate_report(r) { 1 } else { 0 })
-        .sum();
+        .filter(|r| validate_report(r, options))
+        .count();
 
-    num_safe_reports
+    Ok(to_u64(num_safe_reports)?.into())
 }
 
-pub fn part2(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    part2_with(data_path, &Options::default())
+}
 
-    let reports: Vec<Vec<i32>> = text
-        .lines()
-        .map(|l| l.split_whitespace().map(|n| n.parse().unwrap()).collect())
-        .collect();
+pub fn part2_with(data_path: &Path, options: &Options) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    let mut num_safe_reports = 0;
+    let reports = parse_input(&text)?;
 
-    // Same as before, but try dropping each element
-    for r in reports {
-        let mut valid = validate_report(&r);
+    let num_safe_reports = reports
+        .iter()
+        .filter(|r| dampen(r, options, options.max_removals).is_some())
+        .count();
 
-        for i in 0..r.len() {
-            if valid {
-                break;
-            }
-            let mut sub_r = r.clone();
-            sub_r.remove(i); //Change to a filter, this is very expensive!
-            valid = validate_report(&sub_r);
-        }
+    Ok(to_u64(num_safe_reports)?.into())
+}
 
-        num_safe_reports += if valid { 1 } else { 0 };
-    }
+/// The levels removed from each report to make it safe, or `None` for the reports that need
+/// more than `max_removals` levels removed
+pub fn dampened_reports(data_path: &Path, options: &Options) -> Result<Vec<Option<Vec<usize>>>> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    num_safe_reports
+    let reports = parse_input(&text)?;
+    Ok(reports
+        .iter()
+        .map(|r| dampen(r, options, options.max_removals))
+        .collect())
+}
+
+/// Generate `size` reports, mostly safe but some needing (or beyond) the dampener
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..size)
+        .map(|_| {
+            let num_levels = rng.gen_range(5..=8);
+            let direction = if rng.gen_bool(0.5) { 1 } else { -1 };
+            let mut level: i32 = rng.gen_range(10..90);
+            let mut levels = vec![level];
+
+            for _ in 1..num_levels {
+                // Mostly safe steps, with the occasional flat, reversed or too-large one
+                let step = if rng.gen_bool(0.1) {
+                    rng.gen_range(-5..=5)
+                } else {
+                    direction * rng.gen_range(1..=3)
+                };
+                level += step;
+                levels.push(level);
+            }
+
+            let levels: Vec<String> = levels.iter().map(ToString::to_string).collect();
+            levels.join(" ") + "\n"
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -106,14 +245,130 @@ mod tests {
     #[test]
     fn test_part1() {
         let (_d, _f, test_path) = create_test_file();
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 2);
     }
 
     #[test]
     fn test_part2() {
         let (_d, _f, test_path) = create_test_file();
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 4);
     }
+
+    #[test]
+    fn test_options() {
+        let (_d, _f, test_path) = create_test_file();
+        let mut options = Options::default();
+        options.set("max_step", "5").unwrap();
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 4);
+        options.set("min_step", "0").unwrap();
+        assert_eq!(part1_with(&test_path, &options).unwrap(), 5);
+
+        assert!(matches!(
+            options.set("max_step", "x"),
+            Err(Error::Option(_))
+        ));
+        assert!(matches!(options.set("steps", "3"), Err(Error::Option(_))));
+    }
+
+    #[test]
+    fn test_dampen() {
+        let options = Options::default();
+        assert_eq!(dampen(&[7, 6, 4, 2, 1], &options, 1), Some(vec![]));
+        assert_eq!(dampen(&[1, 2, 7, 8, 9], &options, 1), None);
+        assert_eq!(dampen(&[1, 2, 7, 8, 9], &options, 2), Some(vec![0, 1]));
+        assert_eq!(dampen(&[9, 7, 6, 2, 1], &options, 1), None);
+        assert_eq!(dampen(&[1, 3, 2, 4, 5], &options, 1).unwrap().len(), 1);
+        assert_eq!(dampen(&[8, 6, 4, 4, 1], &options, 1).unwrap().len(), 1);
+        assert_eq!(dampen(&[1, 9, 2, 3, 9, 4], &options, 2), Some(vec![1, 4]));
+        assert_eq!(dampen(&[1, 5], &options, 1), Some(vec![1]));
+
+        // Compare with trying every set of levels to remove
+        let is_safe = |levels: &[i32]| {
+            [1, -1].into_iter().any(|direction| {
+                levels
+                    .windows(2)
+                    .all(|w| is_safe_step(w[0], w[1], direction, &options))
+            })
+        };
+        let mut rng = StdRng::seed_from_u64(2024);
+        for _ in 0..2000 {
+            let levels: Vec<i32> = (0..rng.gen_range(1..=8))
+                .map(|_| rng.gen_range(0..12))
+                .collect();
+            let max_removals = rng.gen_range(0..=3);
+            let fewest = (0_u32..1 << levels.len())
+                .filter(|mask| {
+                    let remaining: Vec<i32> = (0..levels.len())
+                        .filter(|i| mask & (1 << i) == 0)
+                        .map(|i| levels[i])
+                        .collect();
+                    is_safe(&remaining)
+                })
+                .map(u32::count_ones)
+                .min()
+                .filter(|&n| n as usize <= max_removals);
+
+            let removed = dampen(&levels, &options, max_removals);
+            assert_eq!(
+                removed.as_ref().map(|r| u32::try_from(r.len()).unwrap()),
+                fewest,
+                "{levels:?}"
+            );
+            if let Some(removed) = removed {
+                let remaining: Vec<i32> = (0..levels.len())
+                    .filter(|i| !removed.contains(i))
+                    .map(|i| levels[i])
+                    .collect();
+                assert!(is_safe(&remaining), "{levels:?} without {removed:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_removals() {
+        let (_d, _f, test_path) = create_test_file();
+        let options = Options {
+            max_removals: 2,
+            ..Options::default()
+        };
+        assert_eq!(part2_with(&test_path, &options).unwrap(), 6);
+        let options = Options {
+            max_removals: 0,
+            ..Options::default()
+        };
+        assert_eq!(part2_with(&test_path, &options).unwrap(), 2);
+
+        let removed = dampened_reports(&test_path, &Options::default()).unwrap();
+        assert_eq!(removed[0], Some(vec![]));
+        assert_eq!(removed[1], None);
+        assert_eq!(removed[3].as_ref().map(Vec::len), Some(1));
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 1000);
+        assert_eq!(test_input, generate(2024, 1000));
+        assert_eq!(test_input.lines().count(), 1000);
+
+        let temp_dir = tempdir().unwrap();
+        let test_path = temp_dir.path().join("test_input.txt");
+        std::fs::write(&test_path, test_input).unwrap();
+        let num_safe = part1(&test_path).unwrap();
+        let num_dampened = part2(&test_path).unwrap();
+        assert!(num_safe != 0 && num_safe != 1000);
+        assert!(num_dampened != 1000);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            parse_input("7 6 4\n\n1 2 3\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(parse_input("7 6 four\n"), Err(Error::Parse(_))));
+        assert!(validate_report(&[5], &Options::default()));
+        assert!(!validate_report(&[i32::MIN, i32::MAX], &Options::default()));
+    }
 }
@@ -1,19 +1,48 @@
+use crate::answer::Answer;
+use crate::error::{Error, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::path::Path;
 
-pub fn part1(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
-
-    // Read in the lock and keys
+/// Read the lock and key schematics, each 7 rows of 5 columns
+pub fn parse_input(text: &str) -> Result<Vec<Vec<Vec<char>>>> {
     let raw_lock_keys: Vec<Vec<char>> = text
         .lines()
         .filter(|l| l.len() > 0)
         .map(|l| l.chars().collect())
         .collect();
 
+    raw_lock_keys
+        .chunks(7)
+        .enumerate()
+        .map(|(i, ls)| {
+            let well_formed = ls.len() == 7
+                && ls
+                    .iter()
+                    .all(|l| l.len() == 5 && l.iter().all(|c| "#.".contains(*c)))
+                && ls[0].iter().all(|&c| c == ls[0][0]);
+            if well_formed {
+                Ok(ls.to_vec())
+            } else {
+                Err(Error::Parse(format!(
+                    "schematic {}: expected a 7 by 5 lock or key",
+                    i + 1
+                )))
+            }
+        })
+        .collect()
+}
+
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
+
+    // Read in the lock and keys
+    let schematics = parse_input(&text)?;
+
     // Process the lock and keys in groups of 7
     // process locks and keys differently
-    let processed_lock_keys: Vec<(&str, Vec<usize>)> = raw_lock_keys
-        .chunks(7)
+    let processed_lock_keys: Vec<(&str, Vec<usize>)> = schematics
+        .iter()
         .map(|ls| match ls[0][0] {
             '#' => {
                 let mut heights = Vec::new();
@@ -59,12 +88,12 @@ pub fn part1(data_path: &Path) -> u32 {
         .map(|(_, l)| l)
         .collect();
 
-    let mut num_pairs = 0;
+    let mut num_pairs: u64 = 0;
     for lock in &locks {
         for key in &keys {
             let mut ok = true;
             for i in 0..5 {
-                if lock[i]+key[i] > 5 {
+                if lock[i] + key[i] > 5 {
                     ok = false;
                     break;
                 }
@@ -75,11 +104,43 @@ pub fn part1(data_path: &Path) -> u32 {
         }
     }
 
-    num_pairs
+    Ok(num_pairs.into())
 }
 
-pub fn part2(data_path: &Path) -> u32 {
-    0
+pub fn part2(_data_path: &Path) -> Result<Answer> {
+    Ok(0_u64.into())
+}
+
+/// Generate `size` lock and key schematics with random pin heights
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let schematics: Vec<String> = (0..size)
+        .map(|_| {
+            let is_lock = rng.gen_bool(0.5);
+            let heights: Vec<usize> = (0..5).map(|_| rng.gen_range(0..=5)).collect();
+
+            // Locks fill down from the top row, keys fill up from the bottom row
+            (0..7)
+                .map(|row| {
+                    let line: String = heights
+                        .iter()
+                        .map(|&h| {
+                            let filled = if is_lock { row <= h } else { row >= 6 - h };
+                            if filled {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect();
+                    line + "\n"
+                })
+                .collect()
+        })
+        .collect();
+
+    schematics.join("\n")
 }
 
 #[cfg(test)]
@@ -144,7 +205,33 @@ mod tests {
 #.#.#
 #####\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 3);
     }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 100);
+        assert_eq!(test_input, generate(2024, 100));
+        assert_eq!(test_input.lines().filter(|l| !l.is_empty()).count(), 700);
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert!(part1(&test_path).unwrap() != 0);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            parse_input("#####\n.####\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            parse_input("##.##\n.####\n.####\n.####\n.#.#.\n.#...\n.....\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            parse_input("#####\n.####\n.###\n.####\n.#.#.\n.#...\n.....\n"),
+            Err(Error::Parse(_))
+        ));
+    }
 }
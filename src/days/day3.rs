@@ -1,58 +1,422 @@
-use regex::Regex;
-use std::cmp::min;
+use crate::answer::Answer;
+use crate::error::{Error, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 use std::path::Path;
+use std::str::FromStr;
 
-fn calc_line_mul_sums(line: &str) -> u32 {
-    let outer_re = Regex::new(r"(mul\(\d{1,3},\d{1,3}\))").unwrap();
-    let inner_re = Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)").unwrap();
+/// An instruction that can be found in the corrupted memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Mul,
+    Add,
+    Do,
+    Dont,
+}
+
+impl Instruction {
+    /// How the instruction is written before its arguments
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Mul => "mul",
+            Self::Add => "add",
+            Self::Do => "do",
+            Self::Dont => "don't",
+        }
+    }
+
+    /// How many arguments the instruction takes
+    pub const fn arity(self) -> usize {
+        match self {
+            Self::Mul | Self::Add => 2,
+            Self::Do | Self::Dont => 0,
+        }
+    }
+
+    /// Whether the instruction gives a number rather than changing the interpreter's state
+    pub const fn has_value(self) -> bool {
+        matches!(self, Self::Mul | Self::Add)
+    }
+
+    /// The number the instruction gives for `args`, if it gives one
+    pub fn value(self, args: &[u64]) -> Result<Option<u64>> {
+        let value = match self {
+            Self::Mul => args.iter().try_fold(1, |acc: u64, &a| acc.checked_mul(a)),
+            Self::Add => args.iter().try_fold(0, |acc: u64, &a| acc.checked_add(a)),
+            Self::Do | Self::Dont => return Ok(None),
+        };
+        value.ok_or(Error::Overflow).map(Some)
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        [Self::Mul, Self::Add, Self::Do, Self::Dont]
+            .into_iter()
+            .find(|instruction| instruction.name() == s)
+            .ok_or_else(|| Error::Option(format!("there is no instruction called {s:?}")))
+    }
+}
+
+pub const PART1_INSTRUCTIONS: [Instruction; 1] = [Instruction::Mul];
+pub const PART2_INSTRUCTIONS: [Instruction; 3] =
+    [Instruction::Mul, Instruction::Do, Instruction::Dont];
+
+/// An argument of an instruction: a number of up to three digits, or a nested instruction
+/// that gives a number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    Number(u64),
+    Call(Instruction, Vec<Self>),
+}
+
+impl Arg {
+    pub fn value(&self) -> Result<u64> {
+        match self {
+            Self::Number(n) => Ok(*n),
+            Self::Call(instruction, args) => evaluate(*instruction, args)?.ok_or_else(|| {
+                Error::Unsupported(format!("{} doesn't give a number", instruction.name()))
+            }),
+        }
+    }
+}
+
+/// The number an instruction gives once its arguments have been worked out
+fn evaluate(instruction: Instruction, args: &[Arg]) -> Result<Option<u64>> {
+    let args = args.iter().map(Arg::value).collect::<Result<Vec<u64>>>()?;
+    instruction.value(&args)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Call(Instruction, Vec<Arg>),
+    /// Memory that isn't an instruction
+    Garbage,
+}
+
+/// A token and the byte offsets it starts and ends at in the memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lexeme {
+    pub token: Token,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// What was found at a position and where it ends, or the position the memory stopped matching
+type Scan<T> = std::result::Result<(T, usize), usize>;
+
+/// Splits corrupted memory into instructions and the garbage between them in one pass
+pub struct Lexer<'a> {
+    memory: &'a [u8],
+    pos: usize,
+    instructions: &'a [Instruction],
+    nested: bool,
+    // An instruction found straight after some garbage, which is given out next
+    pending: Option<Lexeme>,
+    more_to_come: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Look for the `instructions` in `memory`, with instructions as arguments if `nested`
+    pub const fn new(memory: &'a str, instructions: &'a [Instruction], nested: bool) -> Self {
+        Self {
+            memory: memory.as_bytes(),
+            pos: 0,
+            instructions,
+            nested,
+            pending: None,
+            more_to_come: false,
+        }
+    }
+
+    /// Stop before an instruction that might carry on past the end of the memory, for when
+    /// more memory is still to come
+    #[must_use]
+    pub const fn partial(mut self) -> Self {
+        self.more_to_come = true;
+        self
+    }
+
+    /// The byte offset of the first byte that hasn't been turned into a token
+    pub const fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn literal_at(&self, pos: usize, literal: &str) -> Scan<()> {
+        for (i, &b) in literal.as_bytes().iter().enumerate() {
+            if self.memory.get(pos + i) != Some(&b) {
+                return Err(pos + i);
+            }
+        }
+        Ok(((), pos + literal.len()))
+    }
+
+    fn number_at(&self, pos: usize) -> Scan<u64> {
+        let digits = self.memory[pos.min(self.memory.len())..]
+            .iter()
+            .take(3)
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return Err(pos);
+        }
+        let n = self.memory[pos..pos + digits]
+            .iter()
+            .fold(0, |n, &d| n * 10 + u64::from(d - b'0'));
+        Ok((n, pos + digits))
+    }
+
+    fn arg_at(&self, pos: usize) -> Scan<Arg> {
+        match self.number_at(pos) {
+            Ok((n, end)) => Ok((Arg::Number(n), end)),
+            Err(stop) if self.nested => match self.call_at(pos, true) {
+                Ok(((instruction, args), end)) => Ok((Arg::Call(instruction, args), end)),
+                Err(nested_stop) => Err(stop.max(nested_stop)),
+            },
+            Err(stop) => Err(stop),
+        }
+    }
+
+    fn instruction_at(&self, instruction: Instruction, pos: usize) -> Scan<Vec<Arg>> {
+        let ((), mut pos) = self.literal_at(pos, instruction.name())?;
+        ((), pos) = self.literal_at(pos, "(")?;
+        let mut args = vec![];
+        for i in 0..instruction.arity() {
+            if i > 0 {
+                ((), pos) = self.literal_at(pos, ",")?;
+            }
+            let (arg, end) = self.arg_at(pos)?;
+            args.push(arg);
+            pos = end;
+        }
+        ((), pos) = self.literal_at(pos, ")")?;
+        Ok((args, pos))
+    }
+
+    /// The first of the instructions written at `pos`, only looking for the ones that give a
+    /// number if `values_only`
+    fn call_at(&self, pos: usize, values_only: bool) -> Scan<(Instruction, Vec<Arg>)> {
+        let mut stop = pos;
+        for &instruction in self.instructions {
+            if values_only && !instruction.has_value() {
+                continue;
+            }
+            match self.instruction_at(instruction, pos) {
+                Ok((args, end)) => return Ok(((instruction, args), end)),
+                Err(s) => stop = stop.max(s),
+            }
+        }
+        Err(stop)
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Lexeme;
 
-    let mut mul_sums = 0;
+    fn next(&mut self) -> Option<Lexeme> {
+        if let Some(lexeme) = self.pending.take() {
+            return Some(lexeme);
+        }
+
+        let start = self.pos;
+        while self.pos < self.memory.len() {
+            match self.call_at(self.pos, false) {
+                Ok(((instruction, args), end)) => {
+                    let call = Lexeme {
+                        token: Token::Call(instruction, args),
+                        start: self.pos,
+                        end,
+                    };
+                    self.pos = end;
+                    if call.start == start {
+                        return Some(call);
+                    }
+                    let garbage = Lexeme {
+                        token: Token::Garbage,
+                        start,
+                        end: call.start,
+                    };
+                    self.pending = Some(call);
+                    return Some(garbage);
+                }
+                Err(stop) if self.more_to_come && stop >= self.memory.len() => break,
+                Err(_) => self.pos += 1,
+            }
+        }
+
+        (start < self.pos).then_some(Lexeme {
+            token: Token::Garbage,
+            start,
+            end: self.pos,
+        })
+    }
+}
+
+/// Runs instructions, keeping track of whether the ones that give numbers are enabled and
+/// adding up the numbers they give while they are
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpreter {
+    enabled: bool,
+    total: u64,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            total: 0,
+        }
+    }
+}
+
+impl Interpreter {
+    pub fn run(&mut self, token: &Token) -> Result<()> {
+        let Token::Call(instruction, args) = token else {
+            return Ok(());
+        };
+        match instruction {
+            Instruction::Do => self.enabled = true,
+            Instruction::Dont => self.enabled = false,
+            _ if self.enabled => {
+                if let Some(value) = evaluate(*instruction, args)? {
+                    self.total = self.total.checked_add(value).ok_or(Error::Overflow)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub const fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+/// The sum of the numbers the enabled instructions in `memory` give
+pub fn run(memory: &str, instructions: &[Instruction], nested: bool) -> Result<u64> {
+    let mut interpreter = Interpreter::default();
+    for lexeme in Lexer::new(memory, instructions, nested) {
+        interpreter.run(&lexeme.token)?;
+    }
+    Ok(interpreter.total())
+}
+
+/// Run the `instructions` found in memory read a chunk at a time, as if its lines were joined
+/// into one. Only the end of the memory that could still be the start of an instruction is
+/// kept between chunks.
+pub fn run_stream(
+    mut reader: impl BufRead,
+    instructions: &[Instruction],
+    nested: bool,
+) -> Result<u64> {
+    let mut interpreter = Interpreter::default();
+    let mut memory: Vec<u8> = vec![];
+    loop {
+        let chunk = reader.fill_buf()?;
+        let done = chunk.is_empty();
+        for &b in chunk {
+            // Drop line endings, which are "\n" or "\r\n" like for `str::lines`
+            if b == b'\n' {
+                if memory.last() == Some(&b'\r') {
+                    memory.pop();
+                }
+            } else {
+                memory.push(b);
+            }
+        }
+        let num_read = chunk.len();
+        reader.consume(num_read);
+
+        // Hold back a "\r" that could be the start of a line ending, and a character whose
+        // bytes haven't all been read yet
+        let mut end = memory.len();
+        if !done && memory.last() == Some(&b'\r') {
+            end -= 1;
+        }
+        let text = match std::str::from_utf8(&memory[..end]) {
+            Ok(text) => text,
+            Err(e) if !done && e.error_len().is_none() => {
+                std::str::from_utf8(&memory[..e.valid_up_to()]).unwrap_or_default()
+            }
+            Err(_) => {
+                return Err(Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "stream did not contain valid UTF-8",
+                )))
+            }
+        };
+
+        let mut lexer = Lexer::new(text, instructions, nested);
+        if !done {
+            lexer = lexer.partial();
+        }
+        for lexeme in lexer.by_ref() {
+            interpreter.run(&lexeme.token)?;
+        }
+        let consumed = lexer.position();
+        memory.drain(..consumed);
 
-    for cap in outer_re.captures_iter(line) {
-        for inner_cap in inner_re.captures_iter(&cap[1]) {
-            let n1: u32 = inner_cap[1].parse().unwrap();
-            let n2: u32 = inner_cap[2].parse().unwrap();
-            mul_sums += n1 * n2;
+        if done {
+            return Ok(interpreter.total());
         }
     }
+}
 
-    mul_sums
+/// Run the `instructions` found in the corrupted memory, read as one line
+pub fn evaluate_memory(
+    data_path: &Path,
+    instructions: &[Instruction],
+    nested: bool,
+) -> Result<Answer> {
+    let file = File::open(data_path)?;
+    Ok(run_stream(BufReader::new(file), instructions, nested)?.into())
 }
 
-pub fn part1(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
-    text.lines().map(calc_line_mul_sums).sum()
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    // No instruction can span a line, so lines don't need to be joined
+    let text = std::fs::read_to_string(data_path)?;
+    Ok(run(&text, &PART1_INSTRUCTIONS, false)?.into())
 }
 
-pub fn part2(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    evaluate_memory(data_path, &PART2_INSTRUCTIONS, false)
+}
 
-    let mut mul_sums = 0;
+/// Generate corrupted memory containing roughly `size` instructions and garbage fragments
+pub fn generate(seed: u64, size: usize) -> String {
+    const GARBAGE: &[u8] = b"%&!@^*()[]{}<>?,+-_ #'muldont";
 
-    // Very tricky, the input should be treated as just one line
-    // for some reason didn't matter for part1
-    let joined_lines = text
-        .lines()
-        .map(|s| s.to_string())
-        .collect::<Vec<String>>()
-        .join("");
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut memory = String::new();
 
-    let mut l = &joined_lines[..]; //[..] to get &str instead of &String ???
+    for i in 0..size {
+        let a: u32 = rng.gen_range(1..=999);
+        let b: u32 = rng.gen_range(1..=999);
+        let fragment = match rng.gen_range(0..10) {
+            0..=3 => format!("mul({a},{b})"),
+            4 => "do()".to_string(),
+            5 => "don't()".to_string(),
+            // Near misses that shouldn't be counted
+            6 => format!("mul[{a},{b}]"),
+            7 => format!("mul({a},{b}]"),
+            8 => format!("mul({},{b})", rng.gen_range(1000..10_000)),
+            _ => (0..rng.gen_range(1..8))
+                .map(|_| char::from(GARBAGE[rng.gen_range(0..GARBAGE.len())]))
+                .collect(),
+        };
+        memory.push_str(&fragment);
 
-    let mut state = true;
-    while !l.is_empty() {
-        if state {
-            let change_ind = l.find("don't()").unwrap_or(l.len());
-            mul_sums += calc_line_mul_sums(&l[..change_ind]);
-            l = &l[min(change_ind + 7, l.len())..]; //"don't()" is len 7
-            state = false;
-        } else {
-            let change_ind = l.find("do()").unwrap_or(l.len());
-            l = &l[min(change_ind + 4, l.len())..]; //"do()" is len 4
-            state = true;
+        if i % 50 == 49 {
+            memory.push('\n');
         }
     }
-    mul_sums
+    memory.push('\n');
+
+    memory
 }
 
 #[cfg(test)]
@@ -60,7 +424,7 @@ mod tests {
 
     use super::*;
     use std::fs::File;
-    use std::io::Write;
+    use std::io::{Read, Write};
     use std::path::PathBuf;
     use tempfile::tempdir;
 
@@ -79,7 +443,7 @@ mod tests {
         let test_input =
             "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))\n";
         let (_d, _f, path) = create_test_file(test_input);
-        let result = part1(&path);
+        let result = part1(&path).unwrap();
         assert_eq!(result, 161);
     }
 
@@ -88,7 +452,146 @@ mod tests {
         let test_input =
             "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))\n";
         let (_d, _f, path) = create_test_file(test_input);
-        let result = part2(&path);
+        let result = part2(&path).unwrap();
         assert_eq!(result, 48);
     }
+
+    #[test]
+    fn test_lexer() {
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let lexemes: Vec<Lexeme> = Lexer::new(memory, &PART2_INSTRUCTIONS, false).collect();
+        let calls: Vec<(&str, &Token)> = lexemes
+            .iter()
+            .filter(|l| l.token != Token::Garbage)
+            .map(|l| (&memory[l.start..l.end], &l.token))
+            .collect();
+        let mul = |a, b| Token::Call(Instruction::Mul, vec![Arg::Number(a), Arg::Number(b)]);
+        assert_eq!(
+            calls,
+            vec![
+                ("mul(2,4)", &mul(2, 4)),
+                ("don't()", &Token::Call(Instruction::Dont, vec![])),
+                ("mul(5,5)", &mul(5, 5)),
+                ("mul(11,8)", &mul(11, 8)),
+                ("do()", &Token::Call(Instruction::Do, vec![])),
+                ("mul(8,5)", &mul(8, 5)),
+            ]
+        );
+
+        // Garbage fills every gap between instructions
+        assert_eq!(lexemes.first().unwrap().token, Token::Garbage);
+        assert_eq!((lexemes[0].start, lexemes[0].end), (0, 1));
+        assert!(lexemes.windows(2).all(|w| w[0].end == w[1].start));
+        assert_eq!(lexemes.last().unwrap().end, memory.len());
+        assert_eq!(&memory[lexemes[2].start..lexemes[2].end], "&mul[3,7]!^");
+        assert_eq!(Lexer::new("", &PART2_INSTRUCTIONS, false).count(), 0);
+    }
+
+    #[test]
+    fn test_other_instructions() {
+        let memory = "add(2,3)mul(add(1,2),4)mul(mul(2,3),add(4,1000))do()";
+        assert_eq!(run(memory, &PART1_INSTRUCTIONS, false).unwrap(), 6);
+        let instructions = [Instruction::Mul, Instruction::Add];
+        assert_eq!(run(memory, &instructions, false).unwrap(), 5 + 3 + 6);
+        assert_eq!(run(memory, &instructions, true).unwrap(), 5 + 12 + 6);
+
+        // Instructions that don't give a number can't be arguments
+        let instructions = [Instruction::Mul, Instruction::Do];
+        assert_eq!(run("mul(do(),2)", &instructions, true).unwrap(), 0);
+
+        let memory = "mul(999,mul(999,mul(999,mul(999,mul(999,mul(999,mul(999,999)))))))";
+        assert!(matches!(
+            run(memory, &PART1_INSTRUCTIONS, true),
+            Err(Error::Overflow)
+        ));
+
+        assert_eq!("don't".parse::<Instruction>().unwrap(), Instruction::Dont);
+        assert!(matches!(
+            "div".parse::<Instruction>(),
+            Err(Error::Option(_))
+        ));
+    }
+
+    #[test]
+    fn test_stream() {
+        // Small buffers split instructions, line endings and characters between chunks
+        let mut rng = StdRng::seed_from_u64(2024);
+        let pieces = [
+            "mul(", "do()", "don't()", "d", "o", "n't", "(", ")", ",", "7", "42", "1000", "x",
+            "\n", "\r", "\r\n", "é",
+        ];
+        for _ in 0..500 {
+            let memory: String = (0..rng.gen_range(0..80))
+                .map(|_| pieces[rng.gen_range(0..pieces.len())])
+                .collect();
+            let joined: String = memory.lines().collect();
+            let expected = run(&joined, &PART2_INSTRUCTIONS, false).unwrap();
+            for capacity in [1, 2, 3, 7, 64] {
+                let reader = BufReader::with_capacity(capacity, memory.as_bytes());
+                let result = run_stream(reader, &PART2_INSTRUCTIONS, false).unwrap();
+                assert_eq!(
+                    result, expected,
+                    "{memory:?} read {capacity} bytes at a time"
+                );
+            }
+        }
+
+        let reader = BufReader::with_capacity(2, "mul(2,3)\u{e9}mul(1,1)".as_bytes());
+        assert_eq!(run_stream(reader, &PART2_INSTRUCTIONS, false).unwrap(), 7);
+        let reader = BufReader::with_capacity(2, &b"mul(2,3)\xff"[..]);
+        assert!(matches!(
+            run_stream(reader, &PART2_INSTRUCTIONS, false),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_long_stream() {
+        // The same memory over and over, without ever holding all of it
+        struct Repeat {
+            memory: Vec<u8>,
+            times: usize,
+            pos: usize,
+        }
+        impl Read for Repeat {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.times == 0 {
+                    return Ok(0);
+                }
+                let n = buf.len().min(self.memory.len() - self.pos);
+                buf[..n].copy_from_slice(&self.memory[self.pos..self.pos + n]);
+                self.pos += n;
+                if self.pos == self.memory.len() {
+                    (self.pos, self.times) = (0, self.times - 1);
+                }
+                Ok(n)
+            }
+        }
+
+        let memory = generate(2024, 1000);
+        let times = 200;
+        let joined: String = memory.repeat(times).lines().collect();
+        let expected = run(&joined, &PART2_INSTRUCTIONS, false).unwrap();
+        let reader = BufReader::new(Repeat {
+            memory: memory.into_bytes(),
+            times,
+            pos: 0,
+        });
+        assert_eq!(
+            run_stream(reader, &PART2_INSTRUCTIONS, false).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 1000);
+        assert_eq!(test_input, generate(2024, 1000));
+
+        let (_d, _f, path) = create_test_file(&test_input);
+        let all_muls = part1(&path).unwrap();
+        let enabled_muls = part2(&path).unwrap();
+        assert!(all_muls != 0);
+        assert!(enabled_muls != all_muls);
+    }
 }
@@ -1,136 +1,300 @@
+use crate::answer::{to_u64, Answer};
+use crate::error::{Error, Result};
+use crate::parse::grid_lines;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
 use std::path::Path;
+use std::str::FromStr;
 
-fn check_pos(grid: &Vec<&str>, x: i64, y: i64, c: char) -> bool {
-    // Check bounds
-    if y < 0 || x < 0 {
-        return false;
+/// The word search's letters, stored row after row so any letter can be looked up directly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    width: i64,
+    height: i64,
+    letters: Vec<u8>,
+}
+
+impl Grid {
+    /// The letter at (x, y), or `None` off the edge of the grid
+    fn get(&self, x: i64, y: i64) -> Option<u8> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = usize::try_from(y * self.width + x).ok()?;
+        self.letters.get(i).copied()
     }
+}
 
-    let x: usize = x.try_into().unwrap();
-    let y: usize = y.try_into().unwrap();
+/// Which ways a word can be written in the word search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Directions {
+    Orthogonal,
+    Diagonal,
+    All,
+}
 
-    if y >= grid.len() || x >= grid[0].len() {
-        return false;
+impl Directions {
+    /// The step between each letter of a word written in each direction
+    pub const fn steps(self) -> &'static [(i64, i64)] {
+        const ALL: [(i64, i64); 8] = [
+            (0, 1),
+            (1, 0),
+            (0, -1),
+            (-1, 0),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ];
+        match self {
+            Self::Orthogonal => ALL.split_at(4).0,
+            Self::Diagonal => ALL.split_at(4).1,
+            Self::All => &ALL,
+        }
     }
+}
+
+impl FromStr for Directions {
+    type Err = Error;
 
-    if let Some(v) = grid[y].chars().nth(x) {
-        v == c
-    } else {
-        false
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "orthogonal" => Ok(Self::Orthogonal),
+            "diagonal" => Ok(Self::Diagonal),
+            "all" => Ok(Self::All),
+            _ => Err(Error::Option(format!(
+                "directions can be orthogonal, diagonal or all, not {s:?}"
+            ))),
+        }
     }
 }
 
-fn find_char_coords(grid: &Vec<&str>, c: char) -> Vec<(i64, i64)> {
-    let mut xs = vec![];
-    for y in 0..grid.len() {
-        for x in 0..grid[0].len() {
-            if grid[y].chars().nth(x).unwrap() == c {
-                xs.push((x as i64, y as i64));
+/// Letters at fixed offsets from each other, which is found wherever every letter matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    cells: Vec<((i64, i64), char)>,
+}
+
+impl Pattern {
+    /// A word written with `step` between each letter
+    pub fn word(word: &str, step: (i64, i64)) -> Result<Self> {
+        let cells = (0..)
+            .zip(word.chars())
+            .map(|(i, c)| ((i * step.0, i * step.1), c))
+            .collect();
+        Self::new(cells)
+    }
+
+    /// A rectangular stencil of letters, one row per line, where `.` matches anything
+    pub fn stencil(text: &str) -> Result<Self> {
+        let mut cells = vec![];
+        for (y, row) in (0..).zip(grid_lines(text)?) {
+            for (x, c) in (0..).zip(row.chars()) {
+                if c != '.' {
+                    cells.push(((x, y), c));
+                }
+            }
+        }
+        Self::new(cells)
+    }
+
+    fn new(cells: Vec<((i64, i64), char)>) -> Result<Self> {
+        if cells.is_empty() {
+            return Err(Error::Option(
+                "a pattern needs at least one letter".to_string(),
+            ));
+        }
+        Ok(Self { cells })
+    }
+
+    /// Every distinct rotation and reflection of the pattern
+    pub fn orientations(&self) -> Vec<Self> {
+        let mut orientations: Vec<Self> = vec![];
+        let mut shapes = vec![];
+        for reflect in [false, true] {
+            for turns in 0..4 {
+                let cells: Vec<((i64, i64), char)> = self
+                    .cells
+                    .iter()
+                    .map(|&((x, y), c)| {
+                        let (mut x, mut y) = if reflect { (-x, y) } else { (x, y) };
+                        for _ in 0..turns {
+                            (x, y) = (-y, x);
+                        }
+                        ((x, y), c)
+                    })
+                    .collect();
+
+                // Line the turned pattern up with the origin so equal shapes compare equal
+                let min_x = cells.iter().map(|&((x, _), _)| x).min().unwrap_or(0);
+                let min_y = cells.iter().map(|&((_, y), _)| y).min().unwrap_or(0);
+                let cells: Vec<_> = cells
+                    .into_iter()
+                    .map(|((x, y), c)| ((x - min_x, y - min_y), c))
+                    .collect();
+                let mut shape = cells.clone();
+                shape.sort_unstable();
+
+                if !shapes.contains(&shape) {
+                    shapes.push(shape);
+                    orientations.push(Self { cells });
+                }
             }
         }
+        orientations
     }
-    xs
-}
-
-fn count_mas(grid: &Vec<&str>, xy: (i64, i64)) -> u32 {
-    let mut num_mas = 0;
-    let dirs = vec![
-        (0, 1),
-        (1, 0),
-        (0, -1),
-        (-1, 0),
-        (1, 1),
-        (1, -1),
-        (-1, 1),
-        (-1, -1),
-    ];
-    let (x, y) = xy;
-    let mas_chars = "MAS";
-
-    for (dx, dy) in dirs {
-        let mut curr_x = x;
-        let mut curr_y = y;
-        let mut found = true;
-        for c in mas_chars.chars() {
-            curr_x += dx;
-            curr_y += dy;
-            if !check_pos(&grid, curr_x, curr_y, c) {
-                found = false;
-                break;
+
+    /// Where each letter lands if the pattern is offset by `pos`, wrapping around the edges of
+    /// the grid if `wrap` is set
+    fn positions<'a>(
+        &'a self,
+        grid: &'a Grid,
+        pos: (i64, i64),
+        wrap: bool,
+    ) -> impl Iterator<Item = ((i64, i64), char)> + 'a {
+        self.cells.iter().map(move |&((dx, dy), c)| {
+            let (x, y) = (pos.0 + dx, pos.1 + dy);
+            if wrap {
+                ((x.rem_euclid(grid.width), y.rem_euclid(grid.height)), c)
+            } else {
+                ((x, y), c)
             }
+        })
+    }
+
+    /// The (x, y) position of each letter if the pattern is found offset by `pos`
+    fn find_at(&self, grid: &Grid, pos: (i64, i64), wrap: bool) -> Option<Match> {
+        // Most places don't match, so check before collecting the positions
+        if !self
+            .positions(grid, pos, wrap)
+            .all(|((x, y), c)| grid.get(x, y).map(char::from) == Some(c))
+        {
+            return None;
         }
+        self.positions(grid, pos, wrap)
+            .map(|((x, y), _)| Some((usize::try_from(x).ok()?, usize::try_from(y).ok()?)))
+            .collect()
+    }
+}
+
+/// The (x, y) positions of the letters of a pattern found in the word search
+pub type Match = Vec<(usize, usize)>;
 
-        if found {
-            num_mas += 1
-        };
-    }
-
-    num_mas
-}
-
-fn count_crosses(grid: &Vec<&str>, xy: (i64, i64)) -> u32 {
-    let (x, y) = xy;
-    let mut num_crosses = 0;
-    let directions = vec![(1, 1), (0, -2), (-2, 0), (0, 2)];
-    let mas_chars_type1 = "MMSS";
-    let mas_chars_type2 = "SSMM";
-    let mas_chars_type3 = "MSSM";
-    let mas_chars_type4 = "SMMS";
-    let mas_chars_types = vec![
-        mas_chars_type1,
-        mas_chars_type2,
-        mas_chars_type3,
-        mas_chars_type4,
-    ];
-
-    for mas_chars in mas_chars_types {
-        let mut curr_x = x;
-        let mut curr_y = y;
-        let mut found = true;
-
-        for ((dx, dy), c) in directions.iter().zip(mas_chars.chars()) {
-            curr_x += dx;
-            curr_y += dy;
-            if !check_pos(&grid, curr_x, curr_y, c) {
-                found = false;
-                break;
+/// Every place any of the `patterns` is found
+pub fn find_patterns(grid: &Grid, patterns: &[Pattern], wrap: bool) -> Vec<Match> {
+    let mut matches = vec![];
+    let mut seen = HashSet::new();
+    for pattern in patterns {
+        // The first letter has to be on the grid, which limits where the pattern can start
+        let ((dx, dy), _) = pattern.cells[0];
+        for y in -dy..grid.height - dy {
+            for x in -dx..grid.width - dx {
+                let Some(found) = pattern.find_at(grid, (x, y), wrap) else {
+                    continue;
+                };
+                // Wrapping around a small grid can find the same letters in more than one way
+                if !wrap || seen.insert(found.clone()) {
+                    matches.push(found);
+                }
             }
         }
-        if found {
-            num_crosses += 1
-        };
     }
+    matches
+}
 
-    num_crosses
+/// Every place `word` is written in any of the `directions`
+pub fn find_word(
+    grid: &Grid,
+    word: &str,
+    directions: Directions,
+    wrap: bool,
+) -> Result<Vec<Match>> {
+    let patterns = directions
+        .steps()
+        .iter()
+        .map(|&step| Pattern::word(word, step))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(find_patterns(grid, &patterns, wrap))
 }
 
-pub fn part1(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+/// Every place the `stencil` is found in any rotation or reflection
+pub fn find_stencil(grid: &Grid, stencil: &str, wrap: bool) -> Result<Vec<Match>> {
+    let patterns = Pattern::stencil(stencil)?.orientations();
+    Ok(find_patterns(grid, &patterns, wrap))
+}
 
-    // Read in the XMAS grid
-    let grid: Vec<&str> = text.lines().collect();
+/// Read the word search as a rectangular grid of ASCII letters
+pub fn parse_input(text: &str) -> Result<Grid> {
+    let lines = grid_lines(text)?;
+    Ok(Grid {
+        width: i64::try_from(lines[0].len()).map_err(|_| Error::Overflow)?,
+        height: i64::try_from(lines.len()).map_err(|_| Error::Overflow)?,
+        letters: lines.concat().into_bytes(),
+    })
+}
 
-    let x_coords = find_char_coords(&grid, 'X');
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    x_coords.iter().map(|&xy| count_mas(&grid, xy)).sum()
+    // Read in the XMAS grid
+    let grid = parse_input(&text)?;
+
+    let matches = find_word(&grid, "XMAS", Directions::All, false)?;
+    Ok(to_u64(matches.len())?.into())
 }
 
-pub fn part2(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
 
     // Read in the XMAS grid
-    let grid: Vec<&str> = text.lines().collect();
+    let grid = parse_input(&text)?;
+
+    // Two MAS crossing at their A
+    let matches = find_stencil(&grid, "M.S\n.A.\nM.S", false)?;
+    Ok(to_u64(matches.len())?.into())
+}
+
+/// Every place the `word` is written in the word search, as the (x, y) position of each
+/// letter
+pub fn word_matches(
+    data_path: &Path,
+    word: &str,
+    directions: Directions,
+    wrap: bool,
+) -> Result<Vec<Match>> {
+    let text = std::fs::read_to_string(data_path)?;
+    let grid = parse_input(&text)?;
+    find_word(&grid, word, directions, wrap)
+}
+
+/// Every place the `stencil` is found in the word search, as the (x, y) position of each
+/// letter
+pub fn stencil_matches(data_path: &Path, stencil: &str, wrap: bool) -> Result<Vec<Match>> {
+    let text = std::fs::read_to_string(data_path)?;
+    let grid = parse_input(&text)?;
+    find_stencil(&grid, stencil, wrap)
+}
 
-    // Find the A positions
-    let a_coords = find_char_coords(&grid, 'A');
+/// Generate a `size` by `size` word search made up of the letters X, M, A and S
+pub fn generate(seed: u64, size: usize) -> String {
+    const LETTERS: [char; 4] = ['X', 'M', 'A', 'S'];
 
-    a_coords.iter().map(|&xy| count_crosses(&grid, xy)).sum()
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..size)
+        .map(|_| {
+            let row: String = (0..size).map(|_| LETTERS[rng.gen_range(0..4)]).collect();
+            row + "\n"
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::error::Error;
     use std::fs::File;
     use std::io::Write;
     use std::path::PathBuf;
@@ -160,7 +324,7 @@ SAXAMASAAA
 MAMMMXMMMM
 MXMXAXMASX";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 18);
     }
 
@@ -178,7 +342,98 @@ S.S.S.S.S.
 M.M.M.M.M.
 ..........\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 9);
     }
+
+    #[test]
+    fn test_find_word() {
+        let grid = parse_input("XMAS\nSAMX\n").unwrap();
+        let matches = find_word(&grid, "XMAS", Directions::All, false).unwrap();
+        assert_eq!(
+            matches,
+            vec![
+                vec![(0, 0), (1, 0), (2, 0), (3, 0)],
+                vec![(3, 1), (2, 1), (1, 1), (0, 1)]
+            ]
+        );
+        let matches = find_word(&grid, "XMAS", Directions::Diagonal, false).unwrap();
+        assert!(matches.is_empty());
+        let matches = find_word(&grid, "XA", Directions::Diagonal, false).unwrap();
+        assert_eq!(matches, vec![vec![(0, 0), (1, 1)], vec![(3, 1), (2, 0)]]);
+
+        // Words can run off one edge and back onto the other
+        let grid = parse_input("ASXM\n").unwrap();
+        assert!(find_word(&grid, "XMAS", Directions::Orthogonal, false)
+            .unwrap()
+            .is_empty());
+        let matches = find_word(&grid, "XMAS", Directions::Orthogonal, true).unwrap();
+        assert_eq!(matches, vec![vec![(2, 0), (3, 0), (0, 0), (1, 0)]]);
+
+        assert_eq!(
+            "diagonal".parse::<Directions>().unwrap(),
+            Directions::Diagonal
+        );
+        assert!(matches!("up".parse::<Directions>(), Err(Error::Option(_))));
+        assert!(matches!(
+            find_word(&grid, "", Directions::All, false),
+            Err(Error::Option(_))
+        ));
+    }
+
+    #[test]
+    fn test_find_stencil() {
+        // The cross looks the same reflected along the line of either MAS
+        let x_mas = Pattern::stencil("M.S\n.A.\nM.S").unwrap();
+        assert_eq!(x_mas.orientations().len(), 4);
+        let corner = Pattern::stencil("AB\nC.").unwrap();
+        assert_eq!(corner.orientations().len(), 8);
+
+        let grid = parse_input("S.M\n.A.\nS.M\n").unwrap();
+        let matches = find_stencil(&grid, "M.S\n.A.\nM.S", false).unwrap();
+        assert_eq!(matches, vec![vec![(2, 2), (0, 2), (1, 1), (2, 0), (0, 0)]]);
+
+        let grid = parse_input("BA\n.C\n").unwrap();
+        let matches = find_stencil(&grid, "AB\nC.", false).unwrap();
+        assert_eq!(matches.len(), 1);
+        // Every turn of the corner lands on the same letters when wrapping around a 2 by 2 grid
+        let matches = find_stencil(&grid, "AB\nC.", true).unwrap();
+        assert_eq!(matches.len(), 1);
+        let grid = parse_input("B.A\n..C\n...\n").unwrap();
+        assert!(find_stencil(&grid, "AB\nC.", false).unwrap().is_empty());
+        let matches = find_stencil(&grid, "AB\nC.", true).unwrap();
+        assert_eq!(matches, vec![vec![(2, 0), (0, 0), (2, 1)]]);
+
+        assert!(matches!(
+            Pattern::stencil("..\n..\n"),
+            Err(Error::Option(_))
+        ));
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 50);
+        assert_eq!(test_input, generate(2024, 50));
+        assert!(test_input.lines().all(|l| l.len() == 50));
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert!(part1(&test_path).unwrap() != 0);
+        assert!(part2(&test_path).unwrap() != 0);
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = parse_input("XM\nAS\n").unwrap();
+        assert_eq!(grid.get(0, 0), Some(b'X'));
+        assert_eq!(grid.get(1, 1), Some(b'S'));
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.get(0, -1), None);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(parse_input(""), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("XMAS\nXMA\n"), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("XMÅS\nXMAS\n"), Err(Error::Parse(_))));
+    }
 }
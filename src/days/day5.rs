@@ -1,112 +1,368 @@
+use crate::answer::{checked_sum, Answer};
+use crate::error::{Error, Result};
+use crate::parse::number;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
 use std::path::Path;
 
-fn parse_inputs(text: &str) -> (HashMap<&str, HashSet<&str>>, Vec<Vec<&str>>) {
-    let rules_vec: Vec<(&str, &str)> = text
-        .lines()
-        .take_while(|l| l.len() > 0)
-        .map(|l| l.split_once('|').unwrap())
-        .collect();
+/// The updates that contributed to an answer, along with their middle pages
+#[pyclass(get_all, frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdatesReport {
+    pub updates: Vec<Vec<u32>>,
+    pub middle_pages: Vec<u32>,
+    pub total: u64,
+}
+
+impl UpdatesReport {
+    fn new(updates: Vec<Vec<u32>>) -> Result<Self> {
+        let middle_pages: Vec<u32> = updates.iter().map(|u| u[u.len() / 2]).collect();
+        let total = checked_sum(middle_pages.iter().map(|&p| u64::from(p)))?;
+        Ok(Self {
+            updates,
+            middle_pages,
+            total,
+        })
+    }
+}
+
+/// Page ordering rules, from a page to the pages that must come after it
+pub type Rules = HashMap<u32, HashSet<u32>>;
+
+/// A rule as the page that must come first and the page that must come after it
+pub type Rule = (u32, u32);
+
+/// Read the page ordering rules and the updates
+pub fn parse_inputs(text: &str) -> Result<(Rules, Vec<Vec<u32>>)> {
+    let mut rules: Rules = HashMap::new();
+
+    let mut lines = text.lines().enumerate();
+    for (i, l) in lines.by_ref().take_while(|(_, l)| !l.is_empty()) {
+        let Some((prior, latter)) = l.split_once('|') else {
+            return Err(Error::Parse(format!(
+                "line {}: expected a rule like 47|53",
+                i + 1
+            )));
+        };
+        rules
+            .entry(number(prior, i)?)
+            .or_default()
+            .insert(number(latter, i)?);
+    }
+
+    let updates = lines
+        .map(|(i, l)| l.split(',').map(|p| number(p, i)).collect())
+        .collect::<Result<Vec<Vec<u32>>>>()?;
+
+    Ok((rules, updates))
+}
+
+/// The rules that an update breaks, as (prior, latter) pairs where the latter page comes first
+fn violated_rules(rules: &Rules, update: &[u32]) -> Vec<Rule> {
+    // Where each page first appears, so each rule is checked without rescanning the update
+    let mut positions: HashMap<u32, usize> = HashMap::new();
+    for (i, &page) in update.iter().enumerate() {
+        positions.entry(page).or_insert(i);
+    }
+
+    let mut violated = vec![];
+    for (i, &page) in update.iter().enumerate() {
+        for &latter_page in rules.get(&page).into_iter().flatten() {
+            if positions.get(&latter_page).is_some_and(|&j| j < i) {
+                violated.push((page, latter_page));
+            }
+        }
+    }
+    violated.sort_unstable();
+    violated.dedup();
+    violated
+}
+
+/// Whether no page comes after a page that the rules say must follow it
+fn is_ordered(rules: &Rules, update: &[u32]) -> bool {
+    violated_rules(rules, update).is_empty()
+}
+
+/// The page ordering rules, for checking how they relate to each other and to updates
+#[pyclass(frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSet {
+    rules: Rules,
+}
+
+impl RuleSet {
+    pub const fn new(rules: Rules) -> Self {
+        Self { rules }
+    }
+
+    /// The rules that an update breaks, see `violated_rules`
+    pub fn violated_by(&self, update: &[u32]) -> Vec<Rule> {
+        violated_rules(&self.rules, update)
+    }
 
-    let mut rules: HashMap<&str, HashSet<&str>> = HashMap::new();
+    /// Every page that must come after `page`, directly or by following other rules, without
+    /// going through `avoid`
+    fn reachable(&self, page: u32, avoid: u32) -> HashSet<u32> {
+        let mut reached = HashSet::new();
+        let mut to_visit = vec![page];
+        while let Some(p) = to_visit.pop() {
+            for &latter_page in self.rules.get(&p).into_iter().flatten() {
+                if latter_page != avoid && reached.insert(latter_page) {
+                    to_visit.push(latter_page);
+                }
+            }
+        }
+        reached
+    }
+}
+
+#[pymethods]
+impl RuleSet {
+    /// Every rule as a (prior, latter) pair
+    pub fn rules(&self) -> Vec<Rule> {
+        let mut rules: Vec<Rule> = self
+            .rules
+            .iter()
+            .flat_map(|(&prior, latter)| latter.iter().map(move |&l| (prior, l)))
+            .collect();
+        rules.sort_unstable();
+        rules
+    }
 
-    for (prior, latter) in rules_vec {
-        rules.entry(prior).or_insert(HashSet::new()).insert(latter);
+    /// Every page mentioned by a rule
+    pub fn pages(&self) -> Vec<u32> {
+        let mut pages: Vec<u32> = self
+            .rules()
+            .into_iter()
+            .flat_map(<[u32; 2]>::from)
+            .collect();
+        pages.sort_unstable();
+        pages.dedup();
+        pages
     }
 
-    let updates: Vec<Vec<&str>> = text
-        .lines()
-        .skip_while(|l| l.len() > 0)
-        .skip(1)
-        .map(|l| l.split(',').collect::<Vec<&str>>())
-        .collect();
+    /// The rules that are already implied by following other rules
+    pub fn redundant_rules(&self) -> Vec<Rule> {
+        // prior|latter is implied if another page after prior has latter after it too, without
+        // going back through prior, which would only lead to prior|latter itself in a cycle
+        let mut redundant: Vec<Rule> = self
+            .rules
+            .iter()
+            .flat_map(|(&prior, latter)| {
+                let reachable: Vec<(u32, HashSet<u32>)> = latter
+                    .iter()
+                    .filter(|&&p| p != prior)
+                    .map(|&p| (p, self.reachable(p, prior)))
+                    .collect();
+                latter
+                    .iter()
+                    .filter(move |&&l| reachable.iter().any(|(p, r)| *p != l && r.contains(&l)))
+                    .map(move |&l| (prior, l))
+            })
+            .collect();
+        redundant.sort_unstable();
+        redundant
+    }
+
+    /// The order of every page, if the rules decide which of any two pages comes first
+    pub fn total_order(&self) -> Option<Vec<u32>> {
+        let pages = self.pages();
+        let mut num_prior: HashMap<u32, usize> = pages.iter().map(|&p| (p, 0)).collect();
+        for (_, latter) in self.rules() {
+            *num_prior.get_mut(&latter)? += 1;
+        }
+
+        // Kahn's algorithm, which only has one page to choose from at each step for a total order
+        let mut order = Vec::with_capacity(pages.len());
+        let mut ready: Vec<u32> = pages.into_iter().filter(|p| num_prior[p] == 0).collect();
+        while let [page] = ready[..] {
+            order.push(page);
+            ready.clear();
+            for latter_page in self.rules.get(&page).into_iter().flatten() {
+                let prior = num_prior.get_mut(latter_page)?;
+                *prior -= 1;
+                if *prior == 0 {
+                    ready.push(*latter_page);
+                }
+            }
+        }
 
-    (rules, updates)
+        // Either two pages were left unordered or the rest wait on a cycle
+        (order.len() == num_prior.len()).then_some(order)
+    }
+
+    pub fn is_total_order(&self) -> bool {
+        self.total_order().is_some()
+    }
+
+    /// Each update that breaks a rule, along with the rules it breaks
+    pub fn violations(&self, updates: Vec<Vec<u32>>) -> Vec<(Vec<u32>, Vec<Rule>)> {
+        updates
+            .into_iter()
+            .filter_map(|update| {
+                let violated = self.violated_by(&update);
+                (!violated.is_empty()).then_some((update, violated))
+            })
+            .collect()
+    }
 }
 
 fn validate_updates<'a>(
-    rules: &'a HashMap<&'a str, HashSet<&'a str>>,
-    updates: &'a Vec<Vec<&'a str>>,
+    rules: &Rules,
+    updates: &'a [Vec<u32>],
     keep_valid: bool,
-) -> Vec<&'a Vec<&'a str>> {
-    let mut valid_updates = vec![];
-
-    for update in updates {
-        let mut valid = true;
-        for i in 0..update.len() - 1 {
-            for j in i + 1..update.len() {
-                let prior_page = &update[i];
-                let latter_page = &update[j];
-                if let Some(subsequent_pages) = rules.get(latter_page) {
-                    if subsequent_pages.contains(prior_page) {
-                        valid = false;
-                    }
+) -> Vec<&'a Vec<u32>> {
+    updates
+        .iter()
+        .filter(|update| is_ordered(rules, update) == keep_valid)
+        .collect()
+}
+
+/// Put the pages of an update in order with Kahn's algorithm, using only the rules between them
+fn order_update(rules: &Rules, update: &[u32]) -> Result<Vec<u32>> {
+    let mut positions: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (i, &page) in update.iter().enumerate() {
+        positions.entry(page).or_default().push(i);
+    }
+
+    // The pages that must come before and after each page, and how many pages each one is
+    // waiting for
+    let mut prior: Vec<Vec<usize>> = vec![vec![]; update.len()];
+    let mut latter: Vec<Vec<usize>> = vec![vec![]; update.len()];
+    let mut num_prior = vec![0; update.len()];
+    for (i, page) in update.iter().enumerate() {
+        let Some(subsequent_pages) = rules.get(page) else {
+            continue;
+        };
+        for latter_page in subsequent_pages {
+            for &j in positions.get(latter_page).into_iter().flatten() {
+                if j != i {
+                    prior[j].push(i);
+                    latter[i].push(j);
+                    num_prior[j] += 1;
                 }
             }
         }
-        if valid && keep_valid {
-            valid_updates.push(update);
+    }
+
+    // Keep taking a page that isn't waiting for any others, earliest in the update first
+    let mut ready: VecDeque<usize> = (0..update.len()).filter(|&i| num_prior[i] == 0).collect();
+    let mut ordered_update = Vec::with_capacity(update.len());
+    while let Some(i) = ready.pop_front() {
+        ordered_update.push(update[i]);
+        for &j in &latter[i] {
+            num_prior[j] -= 1;
+            if num_prior[j] == 0 {
+                ready.push_back(j);
+            }
         }
-        if !valid && !keep_valid {
-            valid_updates.push(update);
+    }
+
+    // Any page still waiting is waiting on a page that's also still waiting, so going back
+    // through those pages must come round to one of them again, which closes the cycle
+    if ordered_update.len() < update.len() {
+        let waiting = |i: usize| num_prior[i] > 0;
+        let mut visited_at = vec![None; update.len()];
+        let mut path = vec![];
+        let mut page = (0..update.len()).find(|&i| waiting(i));
+        while let Some(i) = page.filter(|&i| visited_at[i].is_none()) {
+            visited_at[i] = Some(path.len());
+            path.push(i);
+            page = prior[i].iter().copied().find(|&j| waiting(j));
         }
+        let start = page.and_then(|i| visited_at[i]).unwrap_or_default();
+        let mut cycle = path.split_off(start);
+        cycle.sort_unstable();
+        let cycle: Vec<u32> = cycle.into_iter().map(|i| update[i]).collect();
+        return Err(Error::Unsupported(format!(
+            "the ordering rules for pages {cycle:?} contain a cycle"
+        )));
     }
 
-    valid_updates
+    Ok(ordered_update)
+}
+
+/// Read the ordering rules as a `RuleSet`, along with the updates to check against them
+pub fn rule_set(data_path: &Path) -> Result<(RuleSet, Vec<Vec<u32>>)> {
+    let text = std::fs::read_to_string(data_path)?;
+
+    let (rules, updates) = parse_inputs(&text)?;
+    Ok((RuleSet::new(rules), updates))
+}
+
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    Ok(part1_detailed(data_path)?.total.into())
 }
 
-pub fn part1(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+pub fn part1_detailed(data_path: &Path) -> Result<UpdatesReport> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    let (rules, updates) = parse_inputs(&text);
+    let (rules, updates) = parse_inputs(&text)?;
     let valid_updates = validate_updates(&rules, &updates, true);
 
-    // sum of middle pages of valid updates
-    valid_updates
-        .iter()
-        .map(|u| u[u.len() / 2].parse::<u32>().unwrap())
-        .sum()
+    // keep the valid updates so the middle pages can be summed
+    UpdatesReport::new(valid_updates.into_iter().cloned().collect())
+}
+
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    Ok(part2_detailed(data_path)?.total.into())
 }
 
-pub fn part2(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+pub fn part2_detailed(data_path: &Path) -> Result<UpdatesReport> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    let (rules, updates) = parse_inputs(&text);
+    let (rules, updates) = parse_inputs(&text)?;
     let invalid_updates = validate_updates(&rules, &updates, false);
 
     // order the updates
-    let mut ordered_updates: Vec<Vec<&str>> = vec![];
-
-    for update in invalid_updates {
-        let mut unordered_update: VecDeque<&str> = update.clone().into();
-        let mut ordered_update: Vec<&str> = vec![];
-        while unordered_update.len() > 0 {
-            let mut valid = true;
-            let prior_page = unordered_update.pop_front().unwrap();
-            for latter_page in &unordered_update {
-                if let Some(subsequent_pages) = rules.get(latter_page) {
-                    if subsequent_pages.contains(prior_page) {
-                        valid = false;
-                        break;
-                    }
-                }
-            }
+    let ordered_updates = invalid_updates
+        .into_iter()
+        .map(|update| order_update(&rules, update))
+        .collect::<Result<Vec<Vec<u32>>>>()?;
 
-            if valid {
-                ordered_update.push(prior_page);
-            } else {
-                unordered_update.push_back(prior_page);
-            }
+    // keep the re-ordered updates so the middle pages can be summed
+    UpdatesReport::new(ordered_updates)
+}
+
+/// Generate a complete set of ordering rules and `size` updates, about half already in order
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // The rules cover every pair of pages, in the order of a random permutation
+    let mut pages: Vec<u32> = (10..100).collect();
+    pages.shuffle(&mut rng);
+    pages.truncate(rng.gen_range(25..=49));
+
+    let mut text = String::new();
+    for (i, prior) in pages.iter().enumerate() {
+        for latter in &pages[i + 1..] {
+            writeln!(text, "{prior}|{latter}").unwrap();
+        }
+    }
+    text.push('\n');
+
+    for _ in 0..size {
+        // Updates always have an odd number of pages so there's a middle page
+        let update_len = 2 * rng.gen_range(2..=11) + 1;
+        let mut update: Vec<u32> = pages
+            .choose_multiple(&mut rng, update_len)
+            .copied()
+            .collect();
+
+        if rng.gen_bool(0.5) {
+            let rank = |p: &u32| pages.iter().position(|q| q == p);
+            update.sort_by_key(rank);
         }
 
-        ordered_updates.push(ordered_update);
+        let update: Vec<String> = update.iter().map(ToString::to_string).collect();
+        writeln!(text, "{}", update.join(",")).unwrap();
     }
 
-    // sum of middle pages of valid updates
-    ordered_updates
-        .iter()
-        .map(|u| u[u.len() / 2].parse::<u32>().unwrap())
-        .sum()
+    text
 }
 
 #[cfg(test)]
@@ -160,14 +416,146 @@ mod tests {
     #[test]
     fn test_part1() {
         let (_d, _f, test_path) = create_test_file();
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 143);
     }
 
     #[test]
     fn test_part2() {
         let (_d, _f, test_path) = create_test_file();
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 123);
     }
+
+    #[test]
+    fn test_part1_detailed() {
+        let (_d, _f, test_path) = create_test_file();
+        let result = part1_detailed(&test_path).unwrap();
+        assert_eq!(
+            result.updates,
+            vec![
+                vec![75, 47, 61, 53, 29],
+                vec![97, 61, 53, 29, 13],
+                vec![75, 29, 13]
+            ]
+        );
+        assert_eq!(result.middle_pages, vec![61, 53, 29]);
+        assert_eq!(result.total, 143);
+    }
+
+    #[test]
+    fn test_part2_detailed() {
+        let (_d, _f, test_path) = create_test_file();
+        let result = part2_detailed(&test_path).unwrap();
+        assert_eq!(
+            result.updates,
+            vec![
+                vec![97, 75, 47, 61, 53],
+                vec![61, 29, 13],
+                vec![97, 75, 47, 29, 13]
+            ]
+        );
+        assert_eq!(result.middle_pages, vec![47, 29, 47]);
+        assert_eq!(result.total, 123);
+    }
+
+    #[test]
+    fn test_rule_set() {
+        let (_d, _f, test_path) = create_test_file();
+        let (rule_set, updates) = rule_set(&test_path).unwrap();
+        assert_eq!(rule_set.rules().len(), 21);
+        assert_eq!(rule_set.pages(), vec![13, 29, 47, 53, 61, 75, 97]);
+
+        // Every pair of pages has a rule, so only the rules between neighbours are needed
+        assert_eq!(
+            rule_set.total_order(),
+            Some(vec![97, 75, 47, 61, 53, 29, 13])
+        );
+        let redundant = rule_set.redundant_rules();
+        assert_eq!(redundant.len(), 21 - 6);
+        assert!(redundant.contains(&(97, 13)));
+        assert!(!redundant.contains(&(97, 75)));
+
+        let violations = rule_set.violations(updates);
+        assert_eq!(
+            violations,
+            vec![
+                (vec![75, 97, 47, 61, 53], vec![(97, 75)]),
+                (vec![61, 13, 29], vec![(29, 13)]),
+                (
+                    vec![97, 13, 75, 29, 47],
+                    vec![(29, 13), (47, 13), (47, 29), (75, 13)]
+                ),
+            ]
+        );
+
+        // Two pages with nothing between them, or a cycle, can't be put in a single order
+        let (rules, _) = parse_inputs("1|2\n1|3\n\n").unwrap();
+        assert!(!RuleSet::new(rules).is_total_order());
+        let (rules, _) = parse_inputs("1|2\n2|3\n3|1\n\n").unwrap();
+        let rule_set = RuleSet::new(rules);
+        assert_eq!(rule_set.total_order(), None);
+        assert!(rule_set.redundant_rules().is_empty());
+
+        // 1|3 only follows from 1|2 and 2|1 by going round the cycle back to 1
+        let (rules, _) = parse_inputs("1|2\n2|1\n1|3\n\n").unwrap();
+        assert!(RuleSet::new(rules).redundant_rules().is_empty());
+        let (rules, _) = parse_inputs("1|2\n2|3\n3|1\n1|3\n\n").unwrap();
+        assert_eq!(RuleSet::new(rules).redundant_rules(), vec![(1, 3)]);
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 100);
+        assert_eq!(test_input, generate(2024, 100));
+
+        let temp_dir = tempdir().unwrap();
+        let test_path = temp_dir.path().join("test_input.txt");
+        std::fs::write(&test_path, test_input).unwrap();
+        let valid = part1_detailed(&test_path).unwrap();
+        let reordered = part2_detailed(&test_path).unwrap();
+        assert_eq!(valid.updates.len() + reordered.updates.len(), 100);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(
+            parse_inputs("47-53\n\n47,53\n"),
+            Err(Error::Parse(_))
+        ));
+        assert!(matches!(
+            parse_inputs("47|53\n\n47,,53\n"),
+            Err(Error::Parse(_))
+        ));
+
+        let cyclic_rules = "1|2\n2|3\n3|1\n\n3,2,1\n";
+        let temp_dir = tempdir().unwrap();
+        let test_path = temp_dir.path().join("test_input.txt");
+        std::fs::write(&test_path, cyclic_rules).unwrap();
+        assert!(matches!(part2(&test_path), Err(Error::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_order_update() {
+        let (rules, _) = parse_inputs("1|2\n2|3\n3|1\n4|2\n\n").unwrap();
+
+        // Only the rules between an update's own pages matter
+        assert_eq!(order_update(&rules, &[2, 1]).unwrap(), vec![1, 2]);
+        assert_eq!(order_update(&rules, &[2, 5, 4]).unwrap(), vec![5, 4, 2]);
+        assert!(is_ordered(&rules, &[5, 4, 2]));
+        assert!(!is_ordered(&rules, &[2, 5, 4]));
+
+        // The error names the pages caught in the cycle, but not the ones ordered before it
+        let Err(Error::Unsupported(reason)) = order_update(&rules, &[3, 2, 4, 1]) else {
+            panic!("expected the cycle to be found");
+        };
+        assert!(reason.contains("[3, 2, 1]"));
+
+        // Nor the ones that are only waiting for the cycle
+        let (rules, _) = parse_inputs("1|2\n2|3\n3|1\n3|5\n5|6\n\n").unwrap();
+        let Err(Error::Unsupported(reason)) = order_update(&rules, &[6, 3, 5, 2, 1]) else {
+            panic!("expected the cycle to be found");
+        };
+        assert!(reason.contains("pages [3, 2, 1]"));
+    }
 }
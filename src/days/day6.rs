@@ -1,8 +1,23 @@
+use crate::answer::{to_u64, Answer};
+use crate::error::{Error, Result};
+use crate::parse::char_grid;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::HashSet;
 use std::path::Path;
 
+/// Grid squares (x, y) behind an answer, in reading order
+#[pyclass(get_all, frozen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardReport {
+    pub positions: Vec<(usize, usize)>,
+    pub count: u64,
+}
+
+/// Where the guard is standing and which way they're facing
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
-struct GuardPosition {
+pub struct GuardPosition {
     x: usize,
     y: usize,
     orientation: char,
@@ -73,11 +88,9 @@ fn next_guard_position(grid: &Vec<Vec<char>>, guard_pos: &GuardPosition) -> Opti
     Some(new_guard_pos)
 }
 
-pub fn part1(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
-
-    // Read in the guard grid
-    let grid: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+/// Read the lab grid along with the guard's starting position
+pub fn parse_input(text: &str) -> Result<(Vec<Vec<char>>, GuardPosition)> {
+    let grid = char_grid(text)?;
 
     let directions = HashSet::from(['^', '>', 'v', '<']);
 
@@ -87,19 +100,34 @@ pub fn part1(data_path: &Path) -> u32 {
     // what's being `move`d?
     // apparently `move` is transfering ownership of y and row into the inner map from flat_map
     // still don't really understand what problem move is avoiding
-    let (x, y, orientation) = grid
+    let squares = grid
         .iter()
         .enumerate()
-        .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &c)| (x, y, c)))
-        .filter(|(_x, _y, c)| directions.contains(c))
-        .next()
-        .unwrap();
-
-    let mut guard = GuardPosition {
-        x: x,
-        y: y,
-        orientation: orientation,
-    };
+        .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &c)| (x, y, c)));
+
+    if let Some((x, y, c)) = squares.clone().find(|(_x, _y, c)| !".#^>v<".contains(*c)) {
+        return Err(Error::Parse(format!(
+            "line {}: unexpected square {c:?} at x={x}",
+            y + 1
+        )));
+    }
+
+    let mut guards = squares.filter(|(_x, _y, c)| directions.contains(c));
+    match (guards.next(), guards.next()) {
+        (Some((x, y, orientation)), None) => Ok((grid, GuardPosition { x, y, orientation })),
+        _ => Err(Error::Parse("expected exactly one guard".to_string())),
+    }
+}
+
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    Ok(part1_detailed(data_path)?.count.into())
+}
+
+pub fn part1_detailed(data_path: &Path) -> Result<GuardReport> {
+    let text = std::fs::read_to_string(data_path)?;
+
+    // Read in the guard grid and the guard's starting position and orientation
+    let (grid, mut guard) = parse_input(&text)?;
 
     let mut guard_positions: HashSet<GuardPosition> = HashSet::new();
     let mut guard_squares: HashSet<(usize, usize)> = HashSet::new();
@@ -116,27 +144,27 @@ pub fn part1(data_path: &Path) -> u32 {
         };
     }
 
-    // Return length of all unique guard squares
-    guard_squares.len().try_into().unwrap()
-}
+    // Return all unique guard squares in reading order
+    let mut positions: Vec<(usize, usize)> = guard_squares.into_iter().collect();
+    positions.sort_by_key(|&(x, y)| (y, x));
 
-pub fn part2(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+    Ok(GuardReport {
+        count: to_u64(positions.len())?,
+        positions,
+    })
+}
 
-    // Read in the guard grid
-    let mut grid: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    Ok(part2_detailed(data_path)?.count.into())
+}
 
-    let directions = HashSet::from(['^', '>', 'v', '<']);
+pub fn part2_detailed(data_path: &Path) -> Result<GuardReport> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    let (start_x, start_y, start_orientation) = grid
-        .iter()
-        .enumerate()
-        .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &c)| (x, y, c)))
-        .filter(|(_x, _y, c)| directions.contains(c))
-        .next()
-        .unwrap();
+    // Read in the guard grid
+    let (mut grid, start_guard) = parse_input(&text)?;
 
-    let mut num_loops = 0;
+    let mut loop_obstacles = vec![];
 
     //Brute force, just try changing every non-obstacle into an obstacle and
     //count how many result in loops
@@ -149,11 +177,7 @@ pub fn part2(data_path: &Path) -> u32 {
             // pretend there's an obstacle here
             grid[y][x] = '#';
 
-            let mut guard = GuardPosition {
-                x: start_x,
-                y: start_y,
-                orientation: start_orientation,
-            };
+            let mut guard = start_guard.clone();
 
             let mut guard_positions: HashSet<GuardPosition> = HashSet::new();
 
@@ -170,7 +194,7 @@ pub fn part2(data_path: &Path) -> u32 {
             }
 
             if in_a_loop {
-                num_loops += 1;
+                loop_obstacles.push((x, y));
             }
 
             // remove the added obstacle
@@ -178,7 +202,32 @@ pub fn part2(data_path: &Path) -> u32 {
         }
     }
 
-    num_loops
+    Ok(GuardReport {
+        count: to_u64(loop_obstacles.len())?,
+        positions: loop_obstacles,
+    })
+}
+
+/// Generate a `size` by `size` lab with scattered obstructions and an upward facing guard
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut grid: Vec<Vec<char>> = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.gen_bool(0.08) { '#' } else { '.' })
+                .collect()
+        })
+        .collect();
+
+    if size > 0 {
+        let (x, y) = (rng.gen_range(0..size), rng.gen_range(0..size));
+        grid[y][x] = '^';
+    }
+
+    grid.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
 }
 
 #[cfg(test)]
@@ -214,7 +263,7 @@ mod tests {
 #.........
 ......#...\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 41);
     }
 
@@ -232,7 +281,82 @@ mod tests {
 #.........
 ......#...\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 6);
     }
+
+    #[test]
+    fn test_part1_detailed() {
+        let test_input = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let result = part1_detailed(&test_path).unwrap();
+        assert_eq!(result.count, 41);
+        assert_eq!(result.positions.len(), 41);
+
+        // The guard walks up from the start to just below the top obstacle, then right
+        assert_eq!(
+            result.positions[..6],
+            [(4, 1), (5, 1), (6, 1), (7, 1), (8, 1), (4, 2)]
+        );
+        assert_eq!(result.positions.last(), Some(&(7, 9)));
+        assert!(result.positions.contains(&(4, 6)));
+        assert!(result.positions.is_sorted_by_key(|&(x, y)| (y, x)));
+    }
+
+    #[test]
+    fn test_part2_detailed() {
+        let test_input = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...\n";
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        let result = part2_detailed(&test_path).unwrap();
+        assert_eq!(
+            result.positions,
+            vec![(3, 6), (6, 7), (7, 7), (1, 8), (3, 8), (7, 9)]
+        );
+        assert_eq!(result.count, 6);
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 30);
+        assert_eq!(test_input, generate(2024, 30));
+        assert_eq!(test_input.matches('^').count(), 1);
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert!(part1(&test_path).unwrap() != 0);
+        assert!(part2(&test_path).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(parse_input("..#\n...\n"), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("^.#\n..v\n"), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("^.#\n..\n"), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("^.@\n...\n"), Err(Error::Parse(_))));
+        let guard = GuardPosition {
+            x: 0,
+            y: 0,
+            orientation: '^',
+        };
+        assert_eq!(parse_input("^").unwrap().1, guard);
+    }
 }
@@ -1,75 +1,303 @@
-use std::collections::VecDeque;
+use crate::answer::{checked_sum, Answer};
+use crate::error::{Error, Result};
+use crate::parse::number;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::Write;
 use std::path::Path;
+use std::str::FromStr;
 
-fn concat_nums(a: usize, b: usize) -> usize {
-    format!("{}{}", a, b).parse().unwrap()
-}
-
-fn valid_equation(target: usize, mut ns: VecDeque<usize>, test: usize, is_part2: bool) -> bool {
-    if let Some(n) = ns.pop_front() {
-        // Recursive case
-        if test > target {
-            // Can return early if test is already too large
-            false
-        } else {
-            // Ugly, but on the first iteration should have test = 1 for mult
-            let test_mult_branch = if test == 0 { 1 } else { test };
-
-            if valid_equation(target, ns.clone(), test + n, is_part2) {
-                true
-            } else if valid_equation(target, ns.clone(), test_mult_branch * n, is_part2) {
-                true
-            } else if is_part2 && valid_equation(target, ns.clone(), concat_nums(test, n), is_part2)
-            {
-                true
-            } else {
-                false
+/// The power of ten that shifts a number left past every digit of `n`
+fn digits_shift(n: u64) -> u128 {
+    10_u128.pow(n.checked_ilog10().unwrap_or(0) + 1)
+}
+
+fn concat_nums(a: u64, b: u64) -> Option<u64> {
+    // Fails (rather than wrapping) when the result is too big for a u64
+    let concatenated = u128::from(a)
+        .checked_mul(digits_shift(b))?
+        .checked_add(u128::from(b))?;
+    u64::try_from(concatenated).ok()
+}
+
+/// An operator that can go between two numbers of an equation, which is evaluated left to right
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Multiply,
+    Concatenate,
+    Subtract,
+    Divide,
+    Xor,
+}
+
+/// The operators the elephants stole for part 1
+pub const PART1_OPERATORS: [Operator; 2] = [Operator::Add, Operator::Multiply];
+
+/// Part 1's operators and the concatenation operator that was hiding nearby
+pub const PART2_OPERATORS: [Operator; 3] =
+    [Operator::Add, Operator::Multiply, Operator::Concatenate];
+
+impl Operator {
+    /// The result of `a` op `b`, or `None` if it isn't a u64 (or divides by zero)
+    pub fn apply(self, a: u64, b: u64) -> Option<u64> {
+        match self {
+            Self::Add => a.checked_add(b),
+            Self::Multiply => a.checked_mul(b),
+            Self::Concatenate => concat_nums(a, b),
+            Self::Subtract => a.checked_sub(b),
+            Self::Divide => a.checked_div(b),
+            Self::Xor => Some(a ^ b),
+        }
+    }
+
+    pub const fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Multiply => "*",
+            Self::Concatenate => "||",
+            Self::Subtract => "-",
+            Self::Divide => "/",
+            Self::Xor => "^",
+        }
+    }
+
+    /// The `a` that makes `a` op `b` equal `result`, if there's exactly one
+    fn undo(self, result: u64, b: u64) -> Option<u64> {
+        match self {
+            Self::Add => result.checked_sub(b),
+            Self::Multiply => (b != 0 && result.is_multiple_of(b)).then(|| result / b),
+            Self::Concatenate => {
+                // Only when the result ends with the digits of b
+                let shift = digits_shift(b);
+                let result = u128::from(result);
+                (result % shift == u128::from(b))
+                    .then(|| u64::try_from(result / shift).ok())
+                    .flatten()
             }
+            Self::Subtract => result.checked_add(b),
+            Self::Divide => None,
+            Self::Xor => Some(result ^ b),
         }
-    } else {
+    }
+
+    /// Whether the result is never smaller than `a` for a non-zero `b`
+    const fn never_decreases(self) -> bool {
+        matches!(self, Self::Add | Self::Multiply | Self::Concatenate)
+    }
+}
+
+impl FromStr for Operator {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        [
+            Self::Add,
+            Self::Multiply,
+            Self::Concatenate,
+            Self::Subtract,
+            Self::Divide,
+            Self::Xor,
+        ]
+        .into_iter()
+        .find(|op| op.symbol() == s)
+        .ok_or_else(|| Error::Option(format!("there is no operator called {s:?}")))
+    }
+}
+
+fn find_operators(
+    target: u64,
+    test: u64,
+    ns: &[u64],
+    operators: &[Operator],
+    can_prune: bool,
+    chosen: &mut Vec<Operator>,
+) -> bool {
+    let Some((&n, rest)) = ns.split_first() else {
         // Base case where there are no more numbers
-        test == target
+        return test == target;
+    };
+
+    // Can return early if test is already too large and can't come back down
+    if can_prune && test > target {
+        return false;
+    }
+
+    // Branches that don't give a u64 can't be part of the equation
+    for &op in operators {
+        if let Some(next) = op.apply(test, n) {
+            chosen.push(op);
+            if find_operators(target, next, rest, operators, can_prune, chosen) {
+                return true;
+            }
+            chosen.pop();
+        }
+    }
+    false
+}
+
+/// A way of finding the operators to put between `ns` to make `target`
+pub type Solver = fn(u64, &[u64], &[Operator]) -> Option<Vec<Operator>>;
+
+/// The operators to put between `ns` to make `target`, if there are any
+pub fn solve_equation(target: u64, ns: &[u64], operators: &[Operator]) -> Option<Vec<Operator>> {
+    // Integer division and multiplying by zero lose the number they're applied to
+    let loses_numbers = operators.contains(&Operator::Divide)
+        || (operators.contains(&Operator::Multiply) && ns.iter().skip(1).any(|&n| n == 0));
+
+    if loses_numbers {
+        solve_forward(target, ns, operators)
+    } else {
+        solve_backward(target, ns, operators)
+    }
+}
+
+/// Try every operator between each pair of numbers, working from the first number
+pub fn solve_forward(target: u64, ns: &[u64], operators: &[Operator]) -> Option<Vec<Operator>> {
+    let (&first, rest) = ns.split_first()?;
+
+    // Multiplying by zero is the only way the allowed operators could shrink the test value
+    let can_prune = operators.iter().all(|op| op.never_decreases()) && !rest.contains(&0);
+
+    let mut chosen = Vec::with_capacity(rest.len());
+    find_operators(target, first, rest, operators, can_prune, &mut chosen).then_some(chosen)
+}
+
+fn undo_operators(
+    target: u64,
+    ns: &[u64],
+    operators: &[Operator],
+    chosen: &mut Vec<Operator>,
+) -> bool {
+    let Some((&n, rest)) = ns.split_last() else {
+        return false;
+    };
+    if rest.is_empty() {
+        // Base case where the first number has to be whatever is left of the target
+        return n == target;
+    }
+
+    // Only operators that could have given the target from some earlier test value are tried
+    for &op in operators {
+        if let Some(prior) = op.undo(target, n) {
+            chosen.push(op);
+            if undo_operators(prior, rest, operators, chosen) {
+                return true;
+            }
+            chosen.pop();
+        }
     }
+    false
 }
-fn parse_input(data_path: &Path) -> Vec<(usize, VecDeque<usize>)> {
-    let text = std::fs::read_to_string(data_path).unwrap();
 
-    let equations: Vec<(usize, VecDeque<usize>)> = text
-        .lines()
-        .map(|l| l.split_once(": ").unwrap())
-        .map(|(t, ns)| {
-            (
-                t.parse().unwrap(),
-                ns.split(" ").map(|n| n.parse().unwrap()).collect(),
-            )
+/// Work back from the target, undoing the operator before each number from the last one
+///
+/// Only gives the same answers as `solve_forward` when no operator loses the number it's
+/// applied to, i.e. without integer division or multiplying by zero
+pub fn solve_backward(target: u64, ns: &[u64], operators: &[Operator]) -> Option<Vec<Operator>> {
+    let mut chosen = Vec::with_capacity(ns.len());
+    if !undo_operators(target, ns, operators, &mut chosen) {
+        return None;
+    }
+
+    // The operators were undone from last to first
+    chosen.reverse();
+    Some(chosen)
+}
+
+/// Write out an equation like the puzzle does, e.g. `81 + 40 * 27`
+pub fn expression(ns: &[u64], operators: &[Operator]) -> String {
+    let mut expression = ns.first().map(ToString::to_string).unwrap_or_default();
+    for (n, op) in ns.iter().skip(1).zip(operators) {
+        write!(expression, " {} {n}", op.symbol()).unwrap();
+    }
+    expression
+}
+
+/// Read the calibration equations as (test value, operands)
+pub fn parse_input(text: &str) -> Result<Vec<(u64, Vec<u64>)>> {
+    text.lines()
+        .enumerate()
+        .map(|(i, l)| {
+            let Some((t, ns)) = l.split_once(": ") else {
+                return Err(Error::Parse(format!(
+                    "line {}: expected a test value and ': '",
+                    i + 1
+                )));
+            };
+            let ns = ns
+                .split(' ')
+                .map(|n| number(n, i))
+                .collect::<Result<Vec<u64>>>()?;
+            Ok((number(t, i)?, ns))
         })
-        .collect();
+        .collect()
+}
+
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    calibrate(data_path, &PART1_OPERATORS)
+}
 
-    equations
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    calibrate(data_path, &PART2_OPERATORS)
 }
 
-pub fn part1(data_path: &Path) -> u64 {
-    let equations = parse_input(data_path);
+/// Sum the test values of the equations that some of `operators` can make true
+pub fn calibrate(data_path: &Path, operators: &[Operator]) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
+    let equations = parse_input(&text)?;
 
-    let summed_results: usize = equations
-        .iter()
-        .filter(|(test, ns)| valid_equation(*test, ns.clone(), 0, false))
-        .map(|(test, _)| test)
-        .sum();
+    let summed_results = checked_sum(
+        equations
+            .iter()
+            .filter(|(test, ns)| solve_equation(*test, ns, operators).is_some())
+            .map(|(test, _)| *test),
+    )?;
 
-    summed_results.try_into().unwrap()
+    Ok(summed_results.into())
 }
 
-pub fn part2(data_path: &Path) -> u64 {
-    let equations = parse_input(data_path);
+/// An expression making each equation true with `operators`, if there is one
+pub fn expressions(data_path: &Path, operators: &[Operator]) -> Result<Vec<Option<String>>> {
+    let text = std::fs::read_to_string(data_path)?;
+    let equations = parse_input(&text)?;
 
-    let summed_results: usize = equations
+    Ok(equations
         .iter()
-        .filter(|(test, ns)| valid_equation(*test, ns.clone(), 0, true))
-        .map(|(test, _)| test)
-        .sum();
+        .map(|(test, ns)| solve_equation(*test, ns, operators).map(|ops| expression(ns, &ops)))
+        .collect())
+}
 
-    summed_results.try_into().unwrap()
+/// Generate `size` calibration equations, some of which can't be made true
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut text = String::new();
+
+    for _ in 0..size {
+        // Small enough operands that even concatenating all of them fits in a u64
+        let ns: Vec<u64> = (0..rng.gen_range(2..=7))
+            .map(|_| rng.gen_range(1..100))
+            .collect();
+
+        let mut target = ns[0];
+        for &n in &ns[1..] {
+            target = match rng.gen_range(0..3) {
+                0 => target + n,
+                1 => target * n,
+                _ => concat_nums(target, n).unwrap(),
+            };
+        }
+
+        // Nudge some targets so they (most likely) can't be reached
+        if rng.gen_bool(0.3) {
+            target += 1;
+        }
+
+        let ns: Vec<String> = ns.iter().map(ToString::to_string).collect();
+        writeln!(text, "{target}: {}", ns.join(" ")).unwrap();
+    }
+
+    text
 }
 
 #[cfg(test)]
@@ -104,7 +332,7 @@ mod tests {
 21037: 9 7 18 13
 292: 11 6 16 20\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 3749);
     }
 
@@ -121,7 +349,101 @@ mod tests {
 21037: 9 7 18 13
 292: 11 6 16 20\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 11387);
     }
+
+    #[test]
+    fn test_expressions() {
+        let test_input = "\
+190: 10 19
+3267: 81 40 27
+83: 17 5
+7290: 6 8 6 15\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        let result = expressions(&test_path, &PART2_OPERATORS).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                Some("10 * 19".to_string()),
+                // 81 + 40 * 27 is also true, but the last operator is tried first
+                Some("81 * 40 + 27".to_string()),
+                None,
+                Some("6 * 8 || 6 * 15".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let operators: Vec<Operator> = ["-", "/", "^"].iter().map(|s| s.parse().unwrap()).collect();
+        let solve = |target| solve_equation(target, &[10, 4, 3], &operators);
+        assert_eq!(solve(4), Some(vec![Operator::Xor, Operator::Divide]));
+        assert_eq!(solve(2), Some(vec![Operator::Subtract, Operator::Divide]));
+        assert_eq!(expression(&[10, 4, 3], &solve(13).unwrap()), "10 ^ 4 ^ 3");
+        assert_eq!(solve(7), None);
+
+        // Multiplying by zero can bring the test value back down
+        assert!(solve_equation(0, &[5, 0], &PART1_OPERATORS).is_some());
+        assert!(matches!("%".parse::<Operator>(), Err(Error::Option(_))));
+    }
+
+    #[test]
+    fn test_solve_backward() {
+        assert_eq!(concat_nums(12, 345), Some(12345));
+        assert_eq!(concat_nums(0, 7), Some(7));
+        assert_eq!(concat_nums(1, u64::MAX), None);
+        assert_eq!(Operator::Concatenate.undo(12345, 345), Some(12));
+        assert_eq!(Operator::Concatenate.undo(12345, 45), Some(123));
+        assert_eq!(Operator::Concatenate.undo(12345, 35), None);
+
+        // Both directions find the same equations, whichever operators are allowed
+        let mut rng = StdRng::seed_from_u64(2024);
+        let symbols = ["+", "*", "||", "-", "^"];
+        for _ in 0..2000 {
+            let operators: Vec<Operator> = symbols
+                .iter()
+                .filter(|_| rng.gen_bool(0.5))
+                .map(|s| s.parse().unwrap())
+                .collect();
+            let ns: Vec<u64> = (0..rng.gen_range(1..=5))
+                .map(|_| rng.gen_range(1..20))
+                .collect();
+            let target = rng.gen_range(0..2000);
+
+            let forward = solve_forward(target, &ns, &operators);
+            let backward = solve_backward(target, &ns, &operators);
+            assert_eq!(
+                forward.is_some(),
+                backward.is_some(),
+                "{target}: {ns:?} {operators:?}"
+            );
+            if let Some(ops) = backward {
+                let result = ns[1..]
+                    .iter()
+                    .zip(&ops)
+                    .try_fold(ns[0], |test, (&n, op)| op.apply(test, n));
+                assert_eq!(result, Some(target));
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 200);
+        assert_eq!(test_input, generate(2024, 200));
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        let add_mul = part1(&test_path).unwrap();
+        let with_concat = part2(&test_path).unwrap();
+        assert!(add_mul != 0);
+        assert_ne!(add_mul, with_concat);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(parse_input("190 10 19\n"), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("190: 10  19\n"), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("-190: 10 19\n"), Err(Error::Parse(_))));
+    }
 }
@@ -1,8 +1,19 @@
+use crate::answer::{to_u64, Answer};
+use crate::error::{Error, Result};
+use crate::parse::char_grid;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-fn get_antenna_by_freq(grid: &Vec<Vec<char>>) -> HashMap<char, Vec<(i32, i32)>> {
+/// A position on the map, as a number that can be stepped off the edge of the map
+fn coord(n: usize) -> Result<i32> {
+    n.try_into()
+        .map_err(|_| Error::Unsupported(format!("maps can be at most {} squares across", i32::MAX)))
+}
+
+fn get_antenna_by_freq(grid: &Vec<Vec<char>>) -> Result<HashMap<char, Vec<(i32, i32)>>> {
     // Collect the antenna positions by frequency
     let mut antenna_by_freq: HashMap<char, Vec<(i32, i32)>> = HashMap::new();
 
@@ -12,23 +23,28 @@ fn get_antenna_by_freq(grid: &Vec<Vec<char>>) -> HashMap<char, Vec<(i32, i32)>>
                 antenna_by_freq
                     .entry(grid[y][x])
                     .or_insert(Vec::new())
-                    .push((x.try_into().unwrap(), y.try_into().unwrap()));
+                    .push((coord(x)?, coord(y)?));
             }
         }
     }
 
-    antenna_by_freq
+    Ok(antenna_by_freq)
+}
+
+/// Read the antenna map, one row per line
+pub fn parse_input(text: &str) -> Result<Vec<Vec<char>>> {
+    char_grid(text)
 }
 
-pub fn part1(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
 
     // Read in the antenna grid
-    let grid: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
-    let height_i32: i32 = grid.len().try_into().unwrap();
-    let width_i32: i32 = grid[0].len().try_into().unwrap();
+    let grid = parse_input(&text)?;
+    let height_i32 = coord(grid.len())?;
+    let width_i32 = coord(grid[0].len())?;
 
-    let antenna_by_freq = get_antenna_by_freq(&grid);
+    let antenna_by_freq = get_antenna_by_freq(&grid)?;
 
     let mut antinodes: HashSet<(i32, i32)> = HashSet::new();
 
@@ -65,18 +81,18 @@ pub fn part1(data_path: &Path) -> u32 {
         }
     }
 
-    antinodes.len().try_into().unwrap()
+    Ok(to_u64(antinodes.len())?.into())
 }
 
-pub fn part2(data_path: &Path) -> u32 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
 
     // Read in the antenna grid
-    let grid: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
-    let height_i32: i32 = grid.len().try_into().unwrap();
-    let width_i32: i32 = grid[0].len().try_into().unwrap();
+    let grid = parse_input(&text)?;
+    let height_i32 = coord(grid.len())?;
+    let width_i32 = coord(grid[0].len())?;
 
-    let antenna_by_freq = get_antenna_by_freq(&grid);
+    let antenna_by_freq = get_antenna_by_freq(&grid)?;
 
     let mut antinodes: HashSet<(i32, i32)> = HashSet::new();
 
@@ -108,13 +124,36 @@ pub fn part2(data_path: &Path) -> u32 {
         }
     }
 
-    antinodes.len().try_into().unwrap()
+    Ok(to_u64(antinodes.len())?.into())
+}
+
+/// Generate a `size` by `size` map with antennas of a few different frequencies
+pub fn generate(seed: u64, size: usize) -> String {
+    const FREQUENCIES: &[u8] = b"0123456789abcdefABCDEF";
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..size)
+        .map(|_| {
+            let row: String = (0..size)
+                .map(|_| {
+                    if rng.gen_bool(0.03) {
+                        char::from(FREQUENCIES[rng.gen_range(0..FREQUENCIES.len())])
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            row + "\n"
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::error::Error;
     use std::fs::File;
     use std::io::Write;
     use std::path::PathBuf;
@@ -146,7 +185,7 @@ mod tests {
 ............
 ............\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 14);
     }
 
@@ -166,7 +205,23 @@ mod tests {
 ............
 ............\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 34);
     }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 50);
+        assert_eq!(test_input, generate(2024, 50));
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert!(part1(&test_path).unwrap() != 0);
+        assert!(part2(&test_path).unwrap() != 0);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(parse_input(""), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("..a.\n...\n"), Err(Error::Parse(_))));
+    }
 }
@@ -1,8 +1,13 @@
-use std::collections::HashMap;
+use crate::answer::{checked_sum, to_u64, Answer};
+use crate::error::{Error, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::path::Path;
 
-fn expand_map(ns: &Vec<u32>) -> Vec<Option<u32>> {
-    // Expand the fragmented memory representation into the full map
+/// Expand the fragmented memory representation into the full map of file ids
+pub fn expand_map(ns: &[u32]) -> Vec<Option<u32>> {
     let mut file_id = 0;
     let mut expanded_map: Vec<Option<u32>> = Vec::new();
 
@@ -21,27 +26,158 @@ fn expand_map(ns: &Vec<u32>) -> Vec<Option<u32>> {
     expanded_map
 }
 
-fn checksum(m: Vec<Option<u32>>) -> u64 {
+fn checksum(m: &[Option<u32>]) -> Result<u64> {
     // Return the checksum (must be a better way to do this)
-    m.iter()
+    let block_sums = m
+        .iter()
         .enumerate()
-        .map(|(i, f_n)| {
-            if let Some(n) = f_n {
-                (i as u64) * (*n as u64)
+        .map(|(i, f_n)| match f_n {
+            Some(n) => to_u64(i)?.checked_mul(u64::from(*n)).ok_or(Error::Overflow),
+            None => Ok(0),
+        })
+        .collect::<Result<Vec<u64>>>()?;
+    checked_sum(block_sums)
+}
+
+/// Draw the disk like the puzzle does, e.g. `00...111...2...333.44.5555.6666.777.888899`
+pub fn render(map: &[Option<u32>]) -> Result<String> {
+    // File ids are drawn as a single digit, so only the first 10 files can be drawn
+    map.iter()
+        .map(|block| {
+            block.map_or(Ok('.'), |file_id| {
+                char::from_digit(file_id, 10).ok_or_else(|| {
+                    Error::Unsupported("only disks with at most 10 files can be drawn".to_string())
+                })
+            })
+        })
+        .collect()
+}
+
+/// Whether files are compacted a block at a time (part 1) or as whole files (part 2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Blocks,
+    Files,
+}
+
+/// Every state of the disk while it's compacted, starting from the disk map
+pub struct Compaction {
+    map: Vec<Option<u32>>,
+    strategy: Strategy,
+    started: bool,
+    // The left-most open and right-most used blocks, when moving blocks
+    open_ind: usize,
+    block_ind: usize,
+    // The start and length of each file still to move, when moving whole files
+    files: Vec<(usize, usize)>,
+}
+
+impl Compaction {
+    pub fn new(ns: &[u32], strategy: Strategy) -> Self {
+        let map = expand_map(ns);
+
+        let mut files = vec![];
+        let mut cumulative_ind = 0;
+        for (i, &n) in ns.iter().enumerate() {
+            if i % 2 == 0 {
+                files.push((cumulative_ind, n as usize));
+            }
+            cumulative_ind += n as usize;
+        }
+
+        Self {
+            block_ind: map.len().saturating_sub(1),
+            map,
+            strategy,
+            started: false,
+            open_ind: 0,
+            files,
+        }
+    }
+
+    /// Move the right-most file block into the left-most open block
+    fn move_block(&mut self) -> bool {
+        while self.open_ind < self.block_ind {
+            if self.map[self.open_ind].is_some() {
+                self.open_ind += 1;
+            } else if self.map[self.block_ind].is_none() {
+                self.block_ind -= 1;
             } else {
-                0
+                self.map.swap(self.open_ind, self.block_ind);
+                return true;
             }
-        })
-        .sum()
+        }
+        false
+    }
+
+    /// Move the next file that fits into the left-most open span big enough for it
+    fn move_file(&mut self) -> bool {
+        while let Some((f_start, f_blocks)) = self.files.pop() {
+            let mut num_contiguous_open = 0;
+            for open_ind in 0..f_start {
+                if self.map[open_ind].is_some() {
+                    num_contiguous_open = 0;
+                    continue;
+                }
+
+                num_contiguous_open += 1;
+                if num_contiguous_open == f_blocks {
+                    let open_start = open_ind + 1 - f_blocks;
+                    for transfer_ind in 0..f_blocks {
+                        self.map
+                            .swap(open_start + transfer_ind, f_start + transfer_ind);
+                    }
+                    return true;
+                }
+            }
+        }
+        false
+    }
 }
 
-pub fn part1(data_path: &Path) -> u64 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+impl Iterator for Compaction {
+    type Item = Vec<Option<u32>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            return Some(self.map.clone());
+        }
+
+        let moved = match self.strategy {
+            Strategy::Blocks => self.move_block(),
+            Strategy::Files => self.move_file(),
+        };
+        moved.then(|| self.map.clone())
+    }
+}
 
+/// Read the disk map of alternating file and free space lengths
+pub fn parse_input(text: &str) -> Result<Vec<u32>> {
     // Convert chars to int following this stackoverflow
     // https://stackoverflow.com/questions/43983414/how-to-convert-a-rust-char-to-an-integer-so-that-1-becomes-1
     const RADIX: u32 = 10;
-    let ns: Vec<u32> = text.chars().filter_map(|c| c.to_digit(RADIX)).collect();
+    let ns = text
+        .trim_end()
+        .chars()
+        .map(|c| c.to_digit(RADIX))
+        .collect::<Option<Vec<u32>>>()
+        .ok_or_else(|| Error::Parse("expected a disk map made of digits".to_string()))?;
+
+    // Every file takes up at least one block (free space can be empty)
+    if ns.is_empty() || ns.iter().step_by(2).any(|&n| n == 0) {
+        return Err(Error::Parse(
+            "expected files of at least one block".to_string(),
+        ));
+    }
+
+    Ok(ns)
+}
+
+pub fn part1(data_path: &Path) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
+
+    let ns = parse_input(&text)?;
 
     let mut expanded_map = expand_map(&ns);
 
@@ -65,72 +201,88 @@ pub fn part1(data_path: &Path) -> u64 {
         }
     }
 
-    checksum(expanded_map)
+    Ok(checksum(&expanded_map)?.into())
 }
 
-pub fn part2(data_path: &Path) -> u64 {
-    let text = std::fs::read_to_string(data_path).unwrap();
+/// The checksum of `len` blocks of file `file_id` starting at block `start`
+fn file_checksum(file_id: u64, start: u64, len: u64) -> Result<u64> {
+    // file_id * (start + (start + 1) + ... + (start + len - 1))
+    let block_sum = len
+        .checked_mul(start)
+        .and_then(|s| s.checked_add(len * (len - 1) / 2))
+        .ok_or(Error::Overflow)?;
+    file_id.checked_mul(block_sum).ok_or(Error::Overflow)
+}
 
-    const RADIX: u32 = 10;
-    let ns: Vec<u32> = text.chars().filter_map(|c| c.to_digit(RADIX)).collect();
+pub fn part2(data_path: &Path) -> Result<Answer> {
+    let text = std::fs::read_to_string(data_path)?;
 
-    // Get the start and end coordinates of each file
-    let mut files: HashMap<u32, (u32, u32)> = HashMap::new();
+    let ns = parse_input(&text)?;
 
-    let mut cumulative_ind = 0;
-    let mut file_id = 0;
-    for (i, n) in ns.iter().enumerate() {
+    // Get the start and length of each file, and the starts of the free spans of each size
+    let mut files: Vec<(u64, u32)> = Vec::new();
+    let mut free_spans: [BinaryHeap<Reverse<u64>>; 10] = Default::default();
+
+    let mut cumulative_ind: u64 = 0;
+    for (i, &n) in ns.iter().enumerate() {
         if i % 2 == 0 {
-            files.insert(file_id, (cumulative_ind, cumulative_ind + n - 1));
-            file_id += 1;
+            files.push((cumulative_ind, n));
+        } else if n > 0 {
+            free_spans[n as usize].push(Reverse(cumulative_ind));
         }
-        cumulative_ind += n;
+        cumulative_ind += u64::from(n);
     }
 
-    // Get the fully expanded map (maybe a smart way to avoid this)
-    let mut expanded_map = expand_map(&ns);
+    // De-fragment the disk, moving each file to the left-most free span big enough for it
+    let mut checksums = Vec::with_capacity(files.len());
+    for (file_id, &(f_start, f_blocks)) in files.iter().enumerate().rev() {
+        let mut f_start = f_start;
 
-    // De-fragment the map
-    let max_file_id: u32 = (ns.len() / 2).try_into().unwrap();
-
-    for file_id in (0..=max_file_id).rev() {
-        //use the start and end ind of the file blocks
-        let (f_start, f_end) = files.get(&file_id).unwrap();
-        let f_start: usize = (*f_start).try_into().unwrap();
-        let f_end: usize = (*f_end).try_into().unwrap();
-        let f_blocks = f_end - f_start + 1;
-
-        //find the left-most open span large enough to contain this file
-        let mut open_ind: usize = 0;
-        while open_ind < f_start {
-            if expanded_map[open_ind].is_some() {
-                open_ind += 1;
-            } else {
-                let mut num_contiguous_open = 0;
-                let mut open_end_ind = open_ind;
-                while open_ind < f_start && expanded_map[open_end_ind].is_none() {
-                    num_contiguous_open += 1;
-                    open_end_ind += 1;
-                }
+        // The left-most span of each big enough size is at the top of its heap
+        let left_most = (f_blocks as usize..free_spans.len())
+            .filter_map(|size| free_spans[size].peek().map(|&Reverse(s)| (s, size)))
+            .min();
 
-                if num_contiguous_open >= f_blocks {
-                    break;
-                } else {
-                    open_ind += num_contiguous_open;
-                }
-            }
-        }
+        if let Some((open_ind, size)) = left_most.filter(|&(s, _)| s < f_start) {
+            free_spans[size].pop();
+            f_start = open_ind;
 
-        // If open_ind is less than the f_start, must have found a large enough open block
-        if open_ind < f_start {
-            for transfer_ind in 0..f_blocks {
-                expanded_map[open_ind + transfer_ind] = expanded_map[f_start + transfer_ind];
-                expanded_map[f_start + transfer_ind] = None;
+            // Whatever the file doesn't fill is still free, just smaller and further right
+            let left_over = size - f_blocks as usize;
+            if left_over > 0 {
+                free_spans[left_over].push(Reverse(open_ind + u64::from(f_blocks)));
             }
         }
+
+        // The space a file leaves behind is right of every file still to move, so it's never reused
+        checksums.push(file_checksum(
+            to_u64(file_id)?,
+            f_start,
+            u64::from(f_blocks),
+        )?);
     }
 
-    checksum(expanded_map)
+    Ok(checked_sum(checksums)?.into())
+}
+
+/// Generate a disk map of `size` digits, alternating file and free space lengths
+pub fn generate(seed: u64, size: usize) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut disk_map: String = (0..size)
+        .map(|i| {
+            // Files always take up at least one block, free space can be empty
+            let n = if i % 2 == 0 {
+                rng.gen_range(1..=9)
+            } else {
+                rng.gen_range(0..=9)
+            };
+            char::from_digit(n, 10).unwrap()
+        })
+        .collect();
+    disk_map.push('\n');
+
+    disk_map
 }
 
 #[cfg(test)]
@@ -156,7 +308,7 @@ mod tests {
     fn test_part1() {
         let test_input = "2333133121414131402\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part1(&test_path);
+        let result = part1(&test_path).unwrap();
         assert_eq!(result, 1928);
     }
 
@@ -164,7 +316,95 @@ mod tests {
     fn test_part2() {
         let test_input = "2333133121414131402\n";
         let (_d, _f, test_path) = create_test_file(&test_input);
-        let result = part2(&test_path);
+        let result = part2(&test_path).unwrap();
         assert_eq!(result, 2858);
     }
+
+    #[test]
+    fn test_part2_moves() {
+        // 0..111....22222 has no free span left of a file that's big enough for it
+        let test_input = "12345\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        assert_eq!(part2(&test_path).unwrap(), 132);
+
+        // 0.....1.2. becomes 021....... as file 1 fills what file 2 left of the big span
+        let test_input = "151111\n";
+        let (_d, _f, test_path) = create_test_file(test_input);
+        assert_eq!(part2(&test_path).unwrap(), 2 + 2);
+    }
+
+    #[test]
+    fn test_render() {
+        let ns = parse_input("2333133121414131402\n").unwrap();
+        assert_eq!(
+            render(&expand_map(&ns)).unwrap(),
+            "00...111...2...333.44.5555.6666.777.888899"
+        );
+
+        let ns = parse_input("1010101010101010101010\n").unwrap();
+        assert!(matches!(
+            render(&expand_map(&ns)),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_compaction() {
+        let ns = parse_input("12345\n").unwrap();
+        let states: Vec<String> = Compaction::new(&ns, Strategy::Blocks)
+            .map(|m| render(&m).unwrap())
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                "0..111....22222",
+                "02.111....2222.",
+                "022111....222..",
+                "0221112...22...",
+                "02211122..2....",
+                "022111222......",
+            ]
+        );
+
+        let ns = parse_input("2333133121414131402\n").unwrap();
+        let states: Vec<String> = Compaction::new(&ns, Strategy::Files)
+            .map(|m| render(&m).unwrap())
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                "00...111...2...333.44.5555.6666.777.888899",
+                "0099.111...2...333.44.5555.6666.777.8888..",
+                "0099.1117772...333.44.5555.6666.....8888..",
+                "0099.111777244.333....5555.6666.....8888..",
+                "00992111777.44.333....5555.6666.....8888..",
+            ]
+        );
+
+        // The last state is the compacted disk the parts take the checksum of
+        let compacted = Compaction::new(&ns, Strategy::Blocks).last().unwrap();
+        assert_eq!(checksum(&compacted).unwrap(), 1928);
+    }
+
+    #[test]
+    fn test_generate() {
+        let test_input = generate(2024, 1000);
+        assert_eq!(test_input, generate(2024, 1000));
+
+        let (_d, _f, test_path) = create_test_file(&test_input);
+        assert!(part1(&test_path).unwrap() != 0);
+        assert!(part2(&test_path).unwrap() != 0);
+
+        // Part 2 doesn't rescan the disk for every file, so a million digits is quick
+        let (_d, _f, test_path) = create_test_file(&generate(2024, 1_000_000));
+        assert!(part2(&test_path).unwrap() != 0);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(matches!(parse_input(""), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("2333 133\n"), Err(Error::Parse(_))));
+        assert!(matches!(parse_input("2303\n"), Err(Error::Parse(_))));
+        assert_eq!(parse_input("12\n").unwrap(), vec![1, 2]);
+    }
 }
@@ -0,0 +1,50 @@
+use pyo3::exceptions::{PyIOError, PyOverflowError, PyRuntimeError, PyValueError};
+use pyo3::PyErr;
+use std::fmt;
+
+/// Everything that can go wrong while solving a puzzle
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Mismatch(String),
+    Option(String),
+    Overflow,
+    Parse(String),
+    Unsupported(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read input: {e}"),
+            Self::Mismatch(reason) => write!(f, "solvers disagree: {reason}"),
+            Self::Option(reason) => write!(f, "invalid option: {reason}"),
+            Self::Overflow => write!(f, "arithmetic overflow while computing the answer"),
+            Self::Parse(reason) => write!(f, "invalid input: {reason}"),
+            Self::Unsupported(reason) => write!(f, "input can't be solved: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<Error> for PyErr {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io(_) => PyIOError::new_err(e.to_string()),
+            Error::Mismatch(_) => PyRuntimeError::new_err(e.to_string()),
+            Error::Overflow => PyOverflowError::new_err(e.to_string()),
+            Error::Option(_) | Error::Parse(_) | Error::Unsupported(_) => {
+                PyValueError::new_err(e.to_string())
+            }
+        }
+    }
+}
@@ -1,183 +1,563 @@
+use answer::Answer;
+use options::PuzzleOptions;
 use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict};
+use std::collections::HashMap;
 use std::path::Path;
 
-mod days;
+pub mod answer;
+pub mod days;
+pub mod error;
+pub mod options;
+mod parse;
+
+/// Start from a day's puzzle values and change any options passed as keyword arguments
+fn puzzle_options<T: PuzzleOptions>(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<T> {
+    let mut options = T::default();
+    if let Some(kwargs) = kwargs {
+        for (key, value) in kwargs {
+            // Python spells its flags True and False
+            let value = match value.extract::<bool>() {
+                Ok(flag) if value.is_instance_of::<PyBool>() => flag.to_string(),
+                _ => value.str()?.to_cow()?.into_owned(),
+            };
+            options.set(&key.extract::<String>()?, &value)?;
+        }
+    }
+    Ok(options)
+}
+
+#[pyfunction]
+fn day1_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day1::part1(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day1_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day1::part2(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day1_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day1::generate(seed, size))
+}
+
+#[pyfunction]
+#[pyo3(signature = (data_path, **options))]
+fn day2_part1(data_path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<Answer> {
+    let options = puzzle_options(options)?;
+    Ok(days::day2::part1_with(Path::new(data_path), &options)?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data_path, **options))]
+fn day2_part2(data_path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<Answer> {
+    let options = puzzle_options(options)?;
+    Ok(days::day2::part2_with(Path::new(data_path), &options)?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data_path, **options))]
+fn day2_dampened(
+    data_path: &str,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Vec<Option<Vec<usize>>>> {
+    let options = puzzle_options(options)?;
+    Ok(days::day2::dampened_reports(
+        Path::new(data_path),
+        &options,
+    )?)
+}
+
+#[pyfunction]
+fn day2_options() -> PyResult<HashMap<&'static str, String>> {
+    Ok(days::day2::Options::default()
+        .values()
+        .into_iter()
+        .collect())
+}
+
+#[pyfunction]
+fn day2_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day2::generate(seed, size))
+}
+
+#[pyfunction]
+fn day3_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day3::part1(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day3_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day3::part2(Path::new(data_path))?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data_path, instructions, nested = false))]
+fn day3_evaluate(data_path: &str, instructions: Vec<String>, nested: bool) -> PyResult<Answer> {
+    let instructions = instructions
+        .into_iter()
+        .map(|s| s.parse())
+        .collect::<Result<Vec<days::day3::Instruction>, _>>()?;
+    Ok(days::day3::evaluate_memory(
+        Path::new(data_path),
+        &instructions,
+        nested,
+    )?)
+}
+
+#[pyfunction]
+fn day3_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day3::generate(seed, size))
+}
+
+#[pyfunction]
+fn day4_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day4::part1(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day4_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day4::part2(Path::new(data_path))?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data_path, word, directions = "all", wrap = false))]
+fn day4_find_word(
+    data_path: &str,
+    word: &str,
+    directions: &str,
+    wrap: bool,
+) -> PyResult<Vec<days::day4::Match>> {
+    let directions = directions.parse()?;
+    Ok(days::day4::word_matches(
+        Path::new(data_path),
+        word,
+        directions,
+        wrap,
+    )?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (data_path, stencil, wrap = false))]
+fn day4_find_stencil(
+    data_path: &str,
+    stencil: &str,
+    wrap: bool,
+) -> PyResult<Vec<days::day4::Match>> {
+    Ok(days::day4::stencil_matches(
+        Path::new(data_path),
+        stencil,
+        wrap,
+    )?)
+}
+
+#[pyfunction]
+fn day4_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day4::generate(seed, size))
+}
+
+#[pyfunction]
+fn day5_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day5::part1(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day5_part1_detailed(data_path: &str) -> PyResult<days::day5::UpdatesReport> {
+    Ok(days::day5::part1_detailed(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day5_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day5::part2(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day5_part2_detailed(data_path: &str) -> PyResult<days::day5::UpdatesReport> {
+    Ok(days::day5::part2_detailed(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day5_rule_set(data_path: &str) -> PyResult<(days::day5::RuleSet, Vec<Vec<u32>>)> {
+    Ok(days::day5::rule_set(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day5_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day5::generate(seed, size))
+}
+
+#[pyfunction]
+fn day6_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day6::part1(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day6_part1_detailed(data_path: &str) -> PyResult<days::day6::GuardReport> {
+    Ok(days::day6::part1_detailed(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day6_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day6::part2(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day6_part2_detailed(data_path: &str) -> PyResult<days::day6::GuardReport> {
+    Ok(days::day6::part2_detailed(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day6_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day6::generate(seed, size))
+}
+
+#[pyfunction]
+fn day7_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day7::part1(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day7_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day7::part2(Path::new(data_path))?)
+}
+
+fn day7_operators(symbols: Vec<String>) -> PyResult<Vec<days::day7::Operator>> {
+    Ok(symbols
+        .into_iter()
+        .map(|s| s.parse())
+        .collect::<Result<_, _>>()?)
+}
+
+#[pyfunction]
+fn day7_calibrate(data_path: &str, operators: Vec<String>) -> PyResult<Answer> {
+    let operators = day7_operators(operators)?;
+    Ok(days::day7::calibrate(Path::new(data_path), &operators)?)
+}
+
+#[pyfunction]
+fn day7_expressions(data_path: &str, operators: Vec<String>) -> PyResult<Vec<Option<String>>> {
+    let operators = day7_operators(operators)?;
+    Ok(days::day7::expressions(Path::new(data_path), &operators)?)
+}
+
+#[pyfunction]
+fn day7_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day7::generate(seed, size))
+}
+
+#[pyfunction]
+fn day8_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day8::part1(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day8_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day8::part2(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day8_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day8::generate(seed, size))
+}
+
+#[pyfunction]
+fn day9_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day9::part1(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day9_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day9::part2(Path::new(data_path))?)
+}
+
+#[pyfunction]
+fn day9_render(disk_map: &str) -> PyResult<String> {
+    let ns = days::day9::parse_input(disk_map)?;
+    Ok(days::day9::render(&days::day9::expand_map(&ns))?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (disk_map, whole_files=false))]
+fn day9_compaction(disk_map: &str, whole_files: bool) -> PyResult<Vec<String>> {
+    let ns = days::day9::parse_input(disk_map)?;
+    let strategy = if whole_files {
+        days::day9::Strategy::Files
+    } else {
+        days::day9::Strategy::Blocks
+    };
+    let states = days::day9::Compaction::new(&ns, strategy);
+    Ok(states
+        .map(|m| days::day9::render(&m))
+        .collect::<Result<_, _>>()?)
+}
 
 #[pyfunction]
-fn day1_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day1::part1(Path::new(data_path)))
+fn day9_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day9::generate(seed, size))
 }
 
 #[pyfunction]
-fn day1_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day1::part2(Path::new(data_path)))
+#[pyo3(signature = (data_path, **options))]
+fn day10_part1(data_path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<Answer> {
+    let options = puzzle_options(options)?;
+    Ok(days::day10::part1_with(Path::new(data_path), &options)?)
 }
 
 #[pyfunction]
-fn day2_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day2::part1(Path::new(data_path)))
+#[pyo3(signature = (data_path, **options))]
+fn day10_part2(data_path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<Answer> {
+    let options = puzzle_options(options)?;
+    Ok(days::day10::part2_with(Path::new(data_path), &options)?)
 }
 
 #[pyfunction]
-fn day2_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day2::part2(Path::new(data_path)))
+#[pyo3(signature = (data_path, limit, **options))]
+fn day10_trails(
+    data_path: &str,
+    limit: usize,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<Vec<Vec<(usize, usize)>>> {
+    let options = puzzle_options(options)?;
+    Ok(days::day10::trails(Path::new(data_path), &options, limit)?)
 }
 
 #[pyfunction]
-fn day3_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day3::part1(Path::new(data_path)))
+fn day10_options() -> PyResult<HashMap<&'static str, String>> {
+    Ok(days::day10::Options::default()
+        .values()
+        .into_iter()
+        .collect())
 }
 
 #[pyfunction]
-fn day3_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day3::part2(Path::new(data_path)))
+fn day10_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day10::generate(seed, size))
 }
 
 #[pyfunction]
-fn day4_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day4::part1(Path::new(data_path)))
+#[pyo3(signature = (data_path, **options))]
+fn day11_part1(data_path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<Answer> {
+    let options = puzzle_options(options)?;
+    Ok(days::day11::part1_with(Path::new(data_path), &options)?)
 }
 
 #[pyfunction]
-fn day4_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day4::part2(Path::new(data_path)))
+#[pyo3(signature = (data_path, **options))]
+fn day11_part2(data_path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<Answer> {
+    let options = puzzle_options(options)?;
+    Ok(days::day11::part2_with(Path::new(data_path), &options)?)
 }
 
 #[pyfunction]
-fn day5_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day5::part1(Path::new(data_path)))
+fn day11_options() -> PyResult<HashMap<&'static str, String>> {
+    Ok(days::day11::Options::default()
+        .values()
+        .into_iter()
+        .collect())
 }
 
 #[pyfunction]
-fn day5_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day5::part2(Path::new(data_path)))
+fn day11_distribution(data_path: &str, blinks: u32) -> PyResult<days::day11::StoneDistribution> {
+    Ok(days::day11::stone_distribution(
+        Path::new(data_path),
+        blinks,
+    )?)
 }
 
 #[pyfunction]
-fn day6_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day6::part1(Path::new(data_path)))
+fn day11_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day11::generate(seed, size))
 }
 
 #[pyfunction]
-fn day6_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day6::part2(Path::new(data_path)))
+fn day12_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day12::part1(Path::new(data_path))?)
 }
 
 #[pyfunction]
-fn day7_part1(data_path: &str) -> PyResult<u64> {
-    Ok(days::day7::part1(Path::new(data_path)))
+fn day12_part1_detailed(data_path: &str) -> PyResult<days::day12::GardenReport> {
+    Ok(days::day12::part1_detailed(Path::new(data_path))?)
 }
 
 #[pyfunction]
-fn day7_part2(data_path: &str) -> PyResult<u64> {
-    Ok(days::day7::part2(Path::new(data_path)))
+fn day12_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day12::part2(Path::new(data_path))?)
 }
 
 #[pyfunction]
-fn day8_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day8::part1(Path::new(data_path)))
+fn day12_part2_detailed(data_path: &str) -> PyResult<days::day12::GardenReport> {
+    Ok(days::day12::part2_detailed(Path::new(data_path))?)
 }
 
 #[pyfunction]
-fn day8_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day8::part2(Path::new(data_path)))
+fn day12_regions(data_path: &str) -> PyResult<Vec<days::day12::Region>> {
+    Ok(days::day12::regions(Path::new(data_path))?)
 }
 
 #[pyfunction]
-fn day9_part1(data_path: &str) -> PyResult<u64> {
-    Ok(days::day9::part1(Path::new(data_path)))
+fn day12_render(data_path: &str) -> PyResult<String> {
+    Ok(days::day12::render(Path::new(data_path))?)
 }
 
 #[pyfunction]
-fn day9_part2(data_path: &str) -> PyResult<u64> {
-    Ok(days::day9::part2(Path::new(data_path)))
+fn day12_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day12::generate(seed, size))
 }
 
 #[pyfunction]
-fn day10_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day10::part1(Path::new(data_path)))
+#[pyo3(signature = (data_path, **options))]
+fn day13_part1(data_path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<Answer> {
+    let options = puzzle_options(options)?;
+    Ok(days::day13::part1_with(Path::new(data_path), &options)?)
 }
 
 #[pyfunction]
-fn day10_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day10::part2(Path::new(data_path)))
+#[pyo3(signature = (data_path, **options))]
+fn day13_part1_detailed(
+    data_path: &str,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<days::day13::ClawReport> {
+    let options = puzzle_options(options)?;
+    Ok(days::day13::part1_detailed(Path::new(data_path), &options)?)
 }
 
 #[pyfunction]
-fn day11_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day11::part1(Path::new(data_path)))
+#[pyo3(signature = (data_path, **options))]
+fn day13_part2(data_path: &str, options: Option<&Bound<'_, PyDict>>) -> PyResult<Answer> {
+    let options = puzzle_options(options)?;
+    Ok(days::day13::part2_with(Path::new(data_path), &options)?)
 }
 
 #[pyfunction]
-fn day11_part2(data_path: &str) -> PyResult<u64> {
-    Ok(days::day11::part2(Path::new(data_path)))
+#[pyo3(signature = (data_path, **options))]
+fn day13_part2_detailed(
+    data_path: &str,
+    options: Option<&Bound<'_, PyDict>>,
+) -> PyResult<days::day13::ClawReport> {
+    let options = puzzle_options(options)?;
+    Ok(days::day13::part2_detailed(Path::new(data_path), &options)?)
 }
 
 #[pyfunction]
-fn day12_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day12::part1(Path::new(data_path)))
+fn day13_options() -> PyResult<HashMap<&'static str, String>> {
+    Ok(days::day13::Options::default()
+        .values()
+        .into_iter()
+        .collect())
 }
 
 #[pyfunction]
-fn day12_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day12::part2(Path::new(data_path)))
+#[pyo3(signature = (seed, size, solvable=None))]
+fn day13_generate(seed: u64, size: usize, solvable: Option<bool>) -> PyResult<String> {
+    Ok(days::day13::generate(seed, size, solvable))
 }
 
 #[pyfunction]
-fn day13_part1(data_path: &str) -> PyResult<u64> {
-    Ok(days::day13::part1(Path::new(data_path)))
+fn day15_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day15::part1(Path::new(data_path))?)
 }
 
 #[pyfunction]
-fn day13_part2(data_path: &str) -> PyResult<u64> {
-    Ok(days::day13::part2(Path::new(data_path)))
+fn day15_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day15::generate(seed, size))
 }
 
 #[pyfunction]
-fn day15_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day15::part1(Path::new(data_path)))
+fn day25_part1(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day25::part1(Path::new(data_path))?)
 }
 
 #[pyfunction]
-fn day25_part1(data_path: &str) -> PyResult<u32> {
-    Ok(days::day25::part1(Path::new(data_path)))
+fn day25_part2(data_path: &str) -> PyResult<Answer> {
+    Ok(days::day25::part2(Path::new(data_path))?)
 }
 
 #[pyfunction]
-fn day25_part2(data_path: &str) -> PyResult<u32> {
-    Ok(days::day25::part2(Path::new(data_path)))
+fn day25_generate(seed: u64, size: usize) -> PyResult<String> {
+    Ok(days::day25::generate(seed, size))
 }
 
 #[pymodule]
 fn advent2024(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(day1_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day1_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day1_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day2_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day2_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day2_dampened, m)?)?;
+    m.add_function(wrap_pyfunction!(day2_options, m)?)?;
+    m.add_function(wrap_pyfunction!(day2_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day3_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day3_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day3_evaluate, m)?)?;
+    m.add_function(wrap_pyfunction!(day3_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day4_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day4_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day4_find_word, m)?)?;
+    m.add_function(wrap_pyfunction!(day4_find_stencil, m)?)?;
+    m.add_function(wrap_pyfunction!(day4_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day5_part1, m)?)?;
+    m.add_function(wrap_pyfunction!(day5_part1_detailed, m)?)?;
     m.add_function(wrap_pyfunction!(day5_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day5_part2_detailed, m)?)?;
+    m.add_function(wrap_pyfunction!(day5_rule_set, m)?)?;
+    m.add_function(wrap_pyfunction!(day5_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day6_part1, m)?)?;
+    m.add_function(wrap_pyfunction!(day6_part1_detailed, m)?)?;
     m.add_function(wrap_pyfunction!(day6_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day6_part2_detailed, m)?)?;
+    m.add_function(wrap_pyfunction!(day6_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day7_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day7_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day7_calibrate, m)?)?;
+    m.add_function(wrap_pyfunction!(day7_expressions, m)?)?;
+    m.add_function(wrap_pyfunction!(day7_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day8_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day8_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day8_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day9_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day9_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day9_render, m)?)?;
+    m.add_function(wrap_pyfunction!(day9_compaction, m)?)?;
+    m.add_function(wrap_pyfunction!(day9_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day10_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day10_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day10_trails, m)?)?;
+    m.add_function(wrap_pyfunction!(day10_options, m)?)?;
+    m.add_function(wrap_pyfunction!(day10_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day11_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day11_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day11_options, m)?)?;
+    m.add_function(wrap_pyfunction!(day11_distribution, m)?)?;
+    m.add_function(wrap_pyfunction!(day11_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day12_part1, m)?)?;
+    m.add_function(wrap_pyfunction!(day12_part1_detailed, m)?)?;
     m.add_function(wrap_pyfunction!(day12_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day12_part2_detailed, m)?)?;
+    m.add_function(wrap_pyfunction!(day12_regions, m)?)?;
+    m.add_function(wrap_pyfunction!(day12_render, m)?)?;
+    m.add_function(wrap_pyfunction!(day12_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day13_part1, m)?)?;
+    m.add_function(wrap_pyfunction!(day13_part1_detailed, m)?)?;
     m.add_function(wrap_pyfunction!(day13_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day13_part2_detailed, m)?)?;
+    m.add_function(wrap_pyfunction!(day13_options, m)?)?;
+    m.add_function(wrap_pyfunction!(day13_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day15_part1, m)?)?;
+    m.add_function(wrap_pyfunction!(day15_generate, m)?)?;
     m.add_function(wrap_pyfunction!(day25_part1, m)?)?;
     m.add_function(wrap_pyfunction!(day25_part2, m)?)?;
+    m.add_function(wrap_pyfunction!(day25_generate, m)?)?;
+    m.add_class::<days::day5::UpdatesReport>()?;
+    m.add_class::<days::day5::RuleSet>()?;
+    m.add_class::<days::day6::GuardReport>()?;
+    m.add_class::<days::day11::StoneDistribution>()?;
+    m.add_class::<days::day11::StoneCache>()?;
+    m.add_class::<days::day12::Region>()?;
+    m.add_class::<days::day12::GardenReport>()?;
+    m.add_class::<days::day13::GameResult>()?;
+    m.add_class::<days::day13::ClawReport>()?;
+    m.add_class::<days::day13::Mismatch>()?;
     Ok(())
 }
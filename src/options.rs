@@ -0,0 +1,23 @@
+use crate::error::{Error, Result};
+use std::str::FromStr;
+
+/// Puzzle constants that can be changed by name, defaulting to the puzzle's values
+pub trait PuzzleOptions: Default {
+    /// Each option's name and current value
+    fn values(&self) -> Vec<(&'static str, String)>;
+
+    /// Change the option called `key` to the number or flag written in `value`
+    fn set(&mut self, key: &str, value: &str) -> Result<()>;
+}
+
+/// Parse the new `value` of the option called `key`
+pub fn value<T: FromStr>(key: &str, value: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| Error::Option(format!("{key} can't be set to {value:?}")))
+}
+
+/// The error for an option that the day doesn't have
+pub fn unknown(key: &str) -> Error {
+    Error::Option(format!("there is no option called {key:?}"))
+}
@@ -0,0 +1,46 @@
+use crate::error::{Error, Result};
+use std::str::FromStr;
+
+/// Parse a number found on the (0-based) `line` of the input
+pub fn number<T: FromStr>(s: &str, line: usize) -> Result<T> {
+    s.parse()
+        .map_err(|_| Error::Parse(format!("line {}: expected a number, found {s:?}", line + 1)))
+}
+
+/// Split the input into the lines of a non-empty rectangular grid of ASCII characters
+pub fn grid_lines(text: &str) -> Result<Vec<&str>> {
+    let lines: Vec<&str> = text.lines().collect();
+
+    let width = lines.first().map_or(0, |l| l.len());
+    if width == 0 {
+        return Err(Error::Parse(
+            "expected a grid of at least one square".to_string(),
+        ));
+    }
+
+    for (y, l) in lines.iter().enumerate() {
+        if !l.is_ascii() {
+            return Err(Error::Parse(format!(
+                "line {}: unexpected non-ASCII character",
+                y + 1
+            )));
+        }
+        if l.len() != width {
+            return Err(Error::Parse(format!(
+                "line {}: expected {width} squares, found {}",
+                y + 1,
+                l.len()
+            )));
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Read a non-empty rectangular grid of characters, one row per line
+pub fn char_grid(text: &str) -> Result<Vec<Vec<char>>> {
+    Ok(grid_lines(text)?
+        .iter()
+        .map(|l| l.chars().collect())
+        .collect())
+}
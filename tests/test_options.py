@@ -0,0 +1,22 @@
+from advent2024 import advent2024
+import pytest
+
+@pytest.fixture
+def test_stones_path(tmp_path):
+    f = tmp_path / "input.txt"
+    f.write_text("125 17\n")
+    return f
+
+def test_default_options():
+    assert advent2024.day11_options() == {"part1_blinks": "25", "part2_blinks": "75"}
+
+def test_options_as_keyword_arguments(test_stones_path):
+    assert advent2024.day11_part1(str(test_stones_path)) == 55312
+    assert advent2024.day11_part1(str(test_stones_path), part1_blinks=6) == 22
+    assert advent2024.day11_part2(str(test_stones_path), part2_blinks="6") == 22
+
+def test_invalid_options(test_stones_path):
+    with pytest.raises(ValueError):
+        advent2024.day11_part1(str(test_stones_path), blinks=6)
+    with pytest.raises(ValueError):
+        advent2024.day11_part1(str(test_stones_path), part1_blinks=-1)
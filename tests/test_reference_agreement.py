@@ -0,0 +1,57 @@
+from advent2024 import advent2024, day1, day2, day3
+from hypothesis import given, settings, strategies as st
+import pytest
+
+# Random but well-formed puzzle inputs for each day that has a python reference solution
+
+location_lists = st.lists(
+    st.tuples(st.integers(0, 99999), st.integers(0, 99999)),
+    min_size=1,
+).map(lambda rows: "".join(f"{l}   {r}\n" for l, r in rows))
+
+# Build reports from small steps so that safe, unsafe and dampenable reports all show up.
+# At least three levels so a report still has a difference after the dampener removes one
+report = st.tuples(
+    st.integers(1, 99),
+    st.lists(st.integers(-4, 4), min_size=2, max_size=7),
+).map(lambda r: [r[0] + sum(r[1][:i]) for i in range(len(r[1]) + 1)])
+
+reports = st.lists(report, min_size=1).map(
+    lambda rs: "".join(" ".join(map(str, r)) + "\n" for r in rs)
+)
+
+instruction = st.one_of(
+    st.builds(lambda a, b: f"mul({a},{b})", st.integers(0, 9999), st.integers(0, 9999)),
+    st.just("do()"),
+    st.just("don't()"),
+)
+garbage = st.text(alphabet="mul(,)don't[]0123456789 %&!", max_size=10)
+
+# The rust solution joins the lines for part 2 while the reference keeps the newlines,
+# so only break lines right after an instruction where nothing can match across them
+corrupted_memory = st.lists(
+    st.tuples(garbage, instruction, st.booleans()),
+    min_size=1,
+).map(lambda fs: "".join(g + i + ("\n" if newline else "") for g, i, newline in fs))
+
+# Add a (day, reference module, input strategy) row here as more python references are written
+REFERENCES = [
+    (1, day1, location_lists),
+    (2, day2, reports),
+    (3, day3, corrupted_memory),
+]
+
+@pytest.mark.parametrize("part", [1, 2])
+@pytest.mark.parametrize("day,reference,inputs", REFERENCES)
+def test_rust_matches_reference(tmp_path, day, reference, inputs, part):
+    rust_solve = getattr(advent2024, f"day{day}_part{part}")
+    py_solve = getattr(reference, f"part{part}")
+    f = tmp_path / "input.txt"
+
+    @settings(max_examples=200, deadline=None)
+    @given(inputs)
+    def check(input_data):
+        f.write_text(input_data)
+        assert rust_solve(str(f)) == py_solve(f)
+
+    check()